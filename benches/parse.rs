@@ -0,0 +1,85 @@
+//! Parsing benchmarks. `parse_recipe` reports throughput for 10 KB, 100 KB and 1 MB cookbooks, so
+//! linear scaling shows up as the same bytes per second at every size.
+//!
+//! Use `just bench-save <name>` and `just bench-compare <name>` to compare a change against a
+//! saved baseline.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use recipe_measures::parser::{parse_measure, parse_recipe, reparse_recipe, TextEdit};
+
+const RECIPE: &str = "Chocolate Chip Cookies
+Makes 24 cookies
+
+Ingredients
+- 1 cup butter, softened
+- ¾ cup brown sugar
+- 2 large eggs
+- 2 ¼ cups flour, about 280g
+- 1½ tsp vanilla extract
+- 1 (12 ounce) bag chocolate chips
+
+1. Preheat the oven to 375°F and line 2 trays with baking paper.
+2. Cream the butter and sugars for 3-4 minutes, then beat in the eggs one at a time.
+3. Stir in the flour and chips. Scoop 2 tbsp balls about 5 cm apart.
+4. Bake for 9 to 11 minutes, until golden. Cool on the tray for 2 mins. Enjoy! 🍪
+
+";
+
+/// Measures with every kind of unit text, spelled out, abbreviated, shouted and unknown
+const MEASURES: [&str; 10] = [
+    "2 tablespoons",
+    "1 TBSP",
+    "3 T",
+    "½ cup",
+    "250 ml",
+    "350 degrees F",
+    "180°C",
+    "2 large eggs",
+    "4 cloves",
+    "1 (12 ounce) bag",
+];
+
+/// A cookbook of about `size` bytes
+fn cookbook(size: usize) -> String {
+    RECIPE.repeat(size / RECIPE.len() + 1)
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_recipe");
+    group.sample_size(10);
+    for size in [10_000, 100_000, 1_000_000] {
+        let text = cookbook(size);
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &text, |b, text| {
+            b.iter(|| parse_recipe(black_box(text.as_str())).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn units(c: &mut Criterion) {
+    c.bench_function("parse_measure", |b| {
+        b.iter(|| {
+            for measure in MEASURES {
+                let _ = black_box(parse_measure(black_box(measure).into()));
+            }
+        })
+    });
+}
+
+/// Typing a char in the middle of a big cookbook
+fn reparse(c: &mut Criterion) {
+    let text = cookbook(1_000_000);
+    let previous = parse_recipe(text.as_str()).unwrap();
+    let middle = text[..text.len() / 2].rfind("2 large").unwrap();
+    let edit = TextEdit::new(middle..middle + 1, "3");
+    let edited = edit.apply(&text);
+
+    c.bench_function("reparse_recipe", |b| {
+        b.iter(|| reparse_recipe(&previous, black_box(&edit), &edited).unwrap())
+    });
+}
+
+criterion_group!(benches, parse, units, reparse);
+criterion_main!(benches);
@@ -0,0 +1,248 @@
+use nom::Finish;
+use num_rational::Rational32;
+use thiserror::Error;
+
+use crate::parser::{parse_number, ParserInput};
+use crate::{Measure, Modifier, Unit, UNITFUL_UNITS};
+
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+    #[error("invalid RecipeML: {0}")]
+    Xml(#[from] roxmltree::Error),
+}
+
+/// A recipe read from one of the older recipe file formats (MealMaster, RecipeML)
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct ArchivedRecipe {
+    pub title: String,
+    pub categories: Vec<String>,
+    /// A count of servings, or of whatever the recipe makes (`36 cookies`)
+    pub yields: Option<Measure>,
+    pub ingredient_groups: Vec<ArchivedGroup>,
+    /// One paragraph per step
+    pub directions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct ArchivedGroup {
+    pub heading: Option<String>,
+    pub ingredients: Vec<ArchivedIngredient>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct ArchivedIngredient {
+    /// The quantity as the file wrote it (`1 1/2`)
+    pub quantity: String,
+    /// The unit code as the file wrote it (`c`, `T`, `lg`)
+    pub unit: String,
+    pub name: String,
+    pub notes: Option<String>,
+    /// [`None`] when there's no quantity or it isn't a number (`2-3`)
+    pub measure: Option<Measure>,
+    pub modifiers: Vec<Modifier>,
+}
+
+/// What a unit code from an archive format stands for
+enum UnitCode {
+    /// A unit we know, scaled by a multiple for units we don't have (`dl` is 100 `ml`)
+    Unit(Unit, Rational32),
+    /// A size (`lg`), so the ingredient is counted
+    Size(Modifier),
+    /// A container or piece (`cn` for can)
+    Counted(&'static str),
+    /// No unit, or `x` for "per serving"
+    Unitless,
+}
+
+/// MealMaster's two letter unit codes, which RecipeML files usually use too
+fn unit_code(code: &str) -> Option<UnitCode> {
+    let unit = |unit| Some(UnitCode::Unit(unit, Rational32::from_integer(1)));
+    let scaled = |unit, numer, denom| Some(UnitCode::Unit(unit, Rational32::new(numer, denom)));
+    match code {
+        "" | "x" | "ea" | "each" => Some(UnitCode::Unitless),
+        "t" | "ts" => unit(Unit::Teaspoon),
+        "T" | "tb" => unit(Unit::Tablespoon),
+        "fl" => scaled(Unit::Tablespoon, 2, 1),
+        "c" => unit(Unit::Cup),
+        "pt" => unit(Unit::Pint),
+        "qt" => unit(Unit::Quart),
+        "ga" => unit(Unit::Gallon),
+        "dr" => unit(Unit::Drop),
+        "ds" => unit(Unit::Dash),
+        "pn" => unit(Unit::Pinch),
+        "ml" | "cb" => unit(Unit::Milliliter),
+        "cl" => scaled(Unit::Milliliter, 10, 1),
+        "dl" => scaled(Unit::Milliliter, 100, 1),
+        "l" => unit(Unit::Liter),
+        "oz" => unit(Unit::Ounce),
+        "lb" => unit(Unit::Pound),
+        "mg" => scaled(Unit::Gram, 1, 1000),
+        "cg" => scaled(Unit::Gram, 1, 100),
+        "dg" => scaled(Unit::Gram, 1, 10),
+        "g" => unit(Unit::Gram),
+        "kg" => unit(Unit::Kilogram),
+        "sm" => Some(UnitCode::Size(Modifier::Small)),
+        "md" => Some(UnitCode::Size(Modifier::Medium)),
+        "lg" => Some(UnitCode::Size(Modifier::Large)),
+        "cn" => Some(UnitCode::Counted("can")),
+        "pk" => Some(UnitCode::Counted("package")),
+        "ct" => Some(UnitCode::Counted("carton")),
+        "bn" => Some(UnitCode::Counted("bunch")),
+        "sl" => Some(UnitCode::Counted("slice")),
+        // RecipeML files often spell their units out
+        word => UNITFUL_UNITS
+            .iter()
+            .find(|unit| {
+                unit.aliases()
+                    .iter()
+                    .any(|alias| alias.eq_ignore_ascii_case(word))
+            })
+            .and_then(|found| unit(found.clone())),
+    }
+}
+
+/// Is this a unit code MealMaster would write
+pub(crate) fn is_unit_code(code: &str) -> bool {
+    code.len() <= 2 && unit_code(code).is_some()
+}
+
+/// The amount and noun of a yield (`36`, `cookies`), which both formats write separately
+pub(crate) fn yield_parts(yields: &Measure) -> Option<(Rational32, &str)> {
+    match yields {
+        Measure::Single(single) => match &single.unit {
+            Unit::Unitless { unit } => Some((single.value, unit)),
+            unit => Some((single.value, unit.abbreviation())),
+        },
+        Measure::Multi(_) => None,
+    }
+}
+
+/// The code MealMaster would write for one of our units
+pub(crate) fn code_for_unit(unit: &Unit) -> Option<&'static str> {
+    let code = match unit {
+        Unit::Drop => "dr",
+        Unit::Dash => "ds",
+        Unit::Pinch => "pn",
+        Unit::Teaspoon => "t",
+        Unit::Tablespoon => "T",
+        Unit::Cup => "c",
+        Unit::Pint => "pt",
+        Unit::Quart => "qt",
+        Unit::Gallon => "ga",
+        Unit::Milliliter => "ml",
+        Unit::Liter => "l",
+        Unit::Ounce => "oz",
+        Unit::Pound => "lb",
+        Unit::Gram => "g",
+        Unit::Kilogram => "kg",
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+impl ArchivedIngredient {
+    /// Build an ingredient from the fields an archive format gives us, working out its measure
+    pub fn new(quantity: &str, unit: &str, name: &str, notes: Option<&str>) -> ArchivedIngredient {
+        let quantity = quantity.trim();
+        let unit = unit.trim();
+        let name = name.trim();
+
+        let value = parse_quantity(quantity);
+        let (measure, modifiers) = match (value, unit_code(unit)) {
+            (Some(value), Some(UnitCode::Unit(unit, multiple))) => {
+                (Some(Measure::single(value * multiple, unit)), vec![])
+            }
+            (Some(value), Some(UnitCode::Size(size))) => (
+                Some(Measure::single(value, Unit::unitless(name.to_string()))),
+                vec![size],
+            ),
+            (Some(value), Some(UnitCode::Counted(counted))) => (
+                Some(Measure::single(value, Unit::unitless(counted.to_string()))),
+                vec![],
+            ),
+            (Some(value), Some(UnitCode::Unitless)) if !name.is_empty() => (
+                Some(Measure::single(value, Unit::unitless(name.to_string()))),
+                vec![],
+            ),
+            _ => (None, vec![]),
+        };
+
+        ArchivedIngredient {
+            quantity: quantity.to_string(),
+            unit: unit.to_string(),
+            name: name.to_string(),
+            notes: notes
+                .map(str::trim)
+                .filter(|notes| !notes.is_empty())
+                .map(ToString::to_string),
+            measure,
+            modifiers,
+        }
+    }
+
+    /// The name and notes the way both formats write them on one line (`Butter, softened`)
+    pub fn text(&self) -> String {
+        match &self.notes {
+            Some(notes) => format!("{}, {notes}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// `1 1/2`, `.5`, `3`, but not ranges like `2-3`
+fn parse_quantity(quantity: &str) -> Option<Rational32> {
+    let quantity = match quantity.strip_prefix('.') {
+        Some(decimal) => format!("0.{decimal}"),
+        None => quantity.to_string(),
+    };
+    let (remainder, value) = parse_number(ParserInput::from(quantity.as_str()))
+        .finish()
+        .ok()?;
+
+    remainder.input.is_empty().then_some(value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unit_codes() {
+        let measure = |quantity, unit, name| ArchivedIngredient::new(quantity, unit, name, None);
+
+        assert_eq!(
+            measure("1 1/2", "T", "sugar").measure,
+            Some(Measure::single(Rational32::new(3, 2), Unit::Tablespoon))
+        );
+        assert_eq!(
+            measure(".5", "t", "salt").measure,
+            Some(Measure::single(Rational32::new(1, 2), Unit::Teaspoon))
+        );
+        assert_eq!(
+            measure("2", "dl", "milk").measure,
+            Some(Measure::single(
+                Rational32::from_integer(200),
+                Unit::Milliliter
+            ))
+        );
+        let eggs = measure("3", "lg", "Eggs");
+        assert_eq!(
+            eggs.measure,
+            Some(Measure::single(
+                Rational32::from_integer(3),
+                Unit::unitless("Eggs".to_string())
+            ))
+        );
+        assert_eq!(eggs.modifiers, vec![Modifier::Large]);
+        assert_eq!(
+            measure("1", "tablespoons", "oil").measure,
+            Some(Measure::single(
+                Rational32::from_integer(1),
+                Unit::Tablespoon
+            ))
+        );
+        assert_eq!(measure("2-3", "c", "flour").measure, None);
+        assert_eq!(measure("", "", "Salt").measure, None);
+    }
+}
@@ -0,0 +1,12 @@
+use std::error::Error;
+
+use lsp_server::Connection;
+
+/// Run the language server over stdin and stdout
+fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
+    let (connection, io_threads) = Connection::stdio();
+    recipe_measures::lsp::run(connection)?;
+    io_threads.join()?;
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use crate::parser::Recipe;
+use crate::{Magnitude, Measure, Rewrite, UnitSystem};
+
+impl<'a> Recipe<'a> {
+    /// Write every measure in the units of `system`, leaving measures that are already in it, or
+    /// that don't belong to a system (times and counts), alone
+    pub fn convert(&self, system: UnitSystem) -> Rewrite {
+        self.rewrite(|token| convert_measure(&token.measure, system))
+    }
+}
+
+/// Convert a single measure, returning [`None`] if it's already in `system` or can't be converted
+/// (including when it's too big for our numbers)
+pub(crate) fn convert_measure(measure: &Measure, system: UnitSystem) -> Option<Measure> {
+    match measure.main_unit().system() {
+        Some(current) if current != system => {
+            Magnitude::checked_from(measure)?.rounded_measure_in(system)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_rational::Rational32;
+
+    use crate::parser::parse_recipe;
+    use crate::Decimal;
+
+    use super::*;
+
+    #[test]
+    fn test_convert() {
+        let recipe = parse_recipe(
+            "Preheat the oven to 350°F. Mix 2 cups flour, 1 tsp salt, 8 oz butter and 3 eggs, \
+            then bake in a 9 inch pan for 30 minutes.",
+        )
+        .unwrap();
+
+        let metric = recipe.convert(UnitSystem::Metric);
+        assert_eq!(
+            metric.text,
+            "Preheat the oven to 175°C. Mix 480 milliliters flour, 5 milliliters salt, \
+            225 grams butter and 3 eggs, then bake in a 23 centimeters pan for 30 minutes."
+        );
+        assert_eq!(metric.changes.len(), 5);
+
+        assert_eq!(recipe.convert(UnitSystem::UsCustomary).text, recipe.raw);
+    }
+
+    #[test]
+    fn test_convert_back() {
+        let recipe = parse_recipe("Add 1.5 L stock, 250 g butter and 15 ml oil at 180°C").unwrap();
+
+        assert_eq!(
+            recipe.convert(UnitSystem::UsCustomary).text,
+            "Add 6 1/4 cups stock, 8 3/4 ounces butter and 1 tablespoon oil at 355°F"
+        );
+    }
+
+    #[test]
+    fn test_convert_too_big() {
+        let recipe = parse_recipe("2000000000 ml water and 2 cups milk").unwrap();
+        assert_eq!(
+            recipe.convert(UnitSystem::UsCustomary).text,
+            "2000000000 ml water and 2 cups milk"
+        );
+        let recipe = parse_recipe("100000 gallons water, 20000 gallons milk, 2 cups rice").unwrap();
+        assert_eq!(
+            recipe.convert(UnitSystem::Metric).text,
+            "100000 gallons water, 76800 liters milk, 480 milliliters rice"
+        );
+        assert_eq!(
+            Decimal(Rational32::new(i32::MAX, 7)).to_string(),
+            "2147483647/7"
+        );
+    }
+}
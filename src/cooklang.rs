@@ -0,0 +1,604 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use nom::branch::alt;
+use nom::bytes::complete::{tag, take, take_till};
+use nom::character::complete::{anychar, char, one_of};
+use nom::combinator::{consumed, map, not, opt, recognize, rest};
+use nom::multi::many0;
+use nom::sequence::{pair, preceded};
+use nom::{Finish, IResult, InputLength, InputTake};
+use num_rational::Rational32;
+use num_traits::One;
+
+use crate::parser::{
+    parse_measure_with, parse_number, Block, ParseOptions, ParserInput, Position, Recipe, Span,
+};
+use crate::{Dimension, Magnitude, Measure, ParseError, Portion, SingleMeasure, Unit};
+
+/// A recipe in [Cooklang](https://cooklang.org) (`Add @flour{2%cups} to the #bowl{}`)
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct Cooklang {
+    /// `>> key: value` lines
+    pub metadata: Vec<(String, String)>,
+    pub steps: Vec<Vec<CooklangItem>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CooklangItem {
+    Text(String),
+    /// `@flour{2%cups}`
+    Ingredient {
+        name: String,
+        quantity: Option<Quantity>,
+    },
+    /// `#pot{}`
+    Cookware {
+        name: String,
+        quantity: Option<Quantity>,
+    },
+    /// `~rest{20%minutes}`
+    Timer {
+        name: Option<String>,
+        quantity: Option<Quantity>,
+    },
+}
+
+/// What's between the braces of a component, like `2%cups`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Quantity {
+    pub raw: String,
+    /// [`None`] when the amount isn't a number (`@salt{a pinch}`)
+    pub measure: Option<Measure>,
+}
+
+impl Cooklang {
+    /// Every ingredient component in the recipe, in order
+    pub fn ingredients(&self) -> impl Iterator<Item = (&str, Option<&Quantity>)> {
+        self.steps.iter().flatten().filter_map(|item| match item {
+            CooklangItem::Ingredient { name, quantity } => Some((name.as_str(), quantity.as_ref())),
+            _ => None,
+        })
+    }
+}
+
+pub fn parse_cooklang(input: &str) -> Result<Cooklang, ParseError> {
+    let mut cooklang = Cooklang::default();
+    let mut step: Vec<CooklangItem> = vec![];
+
+    let mut position = Position::default();
+    let mut in_block_comment = false;
+    for line in input.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let mut line_input = ParserInput::new(content, position);
+        position = position.advance(line);
+
+        if in_block_comment {
+            match content.find("-]") {
+                Some(end) => {
+                    line_input = line_input.advance(end + 2);
+                    in_block_comment = false;
+                }
+                None => continue,
+            }
+        }
+
+        if let Some(metadata) = content.trim_start().strip_prefix(">>") {
+            if let Some((key, value)) = metadata.split_once(':') {
+                let pair = (key.trim().to_string(), value.trim().to_string());
+                cooklang.metadata.push(pair);
+            }
+            continue;
+        }
+        if line_input.input.trim().is_empty() {
+            if !step.is_empty() {
+                cooklang.steps.push(std::mem::take(&mut step));
+            }
+            continue;
+        }
+
+        let (_, (items, unclosed_comment)) =
+            pair(many0(parse_item), opt(tag("[-")))(line_input).finish()?;
+        in_block_comment = unclosed_comment.is_some();
+
+        for item in items.into_iter().flatten() {
+            match (step.last_mut(), item) {
+                (Some(CooklangItem::Text(text)), CooklangItem::Text(more)) => text.push_str(&more),
+                (_, item) => step.push(item),
+            }
+        }
+        // lines in the same step run together
+        match step.last_mut() {
+            Some(CooklangItem::Text(text)) if text.ends_with(char::is_whitespace) => {}
+            Some(CooklangItem::Text(text)) => text.push(' '),
+            _ => step.push(CooklangItem::Text(" ".to_string())),
+        }
+    }
+    if !step.is_empty() {
+        cooklang.steps.push(step);
+    }
+
+    for step in &mut cooklang.steps {
+        if let Some(CooklangItem::Text(text)) = step.last_mut() {
+            text.truncate(text.trim_end().len());
+            if text.is_empty() {
+                step.pop();
+            }
+        }
+    }
+
+    Ok(cooklang)
+}
+
+/// Parse a component or a run of text, returning [`None`] for comments
+fn parse_item(input: ParserInput) -> IResult<ParserInput, Option<CooklangItem>, ParseError> {
+    alt((
+        map(preceded(tag("--"), rest), |_| None),
+        map(block_comment, |_| None),
+        map(parse_component, Some),
+        map(
+            preceded(
+                not(tag("[-")),
+                recognize(pair(anychar, take_till(|c| "@#~-[".contains(c)))),
+            ),
+            |text: ParserInput| Some(CooklangItem::Text(text.input.to_string())),
+        ),
+    ))(input)
+}
+
+/// A `[- comment -]` that ends on the same line
+fn block_comment(input: ParserInput) -> IResult<ParserInput, (), ParseError> {
+    let (remainder, _) = tag("[-")(input)?;
+    match remainder.input.find("-]") {
+        Some(end) => Ok((remainder.advance(end + 2), ())),
+        None => Err(nom::Err::Error(nom::error::ParseError::from_error_kind(
+            input,
+            nom::error::ErrorKind::Tag,
+        ))),
+    }
+}
+
+fn parse_component(input: ParserInput) -> IResult<ParserInput, CooklangItem, ParseError> {
+    let (remainder, sigil) = one_of("@#~")(input)?;
+
+    // names with spaces run up to the braces, single word names don't need them
+    let text = remainder.input;
+    let multiword = text
+        .find('{')
+        .filter(|&end| !text[..end].contains(['@', '#', '~']) && end > 0 || sigil == '~');
+    let name_length = multiword.unwrap_or_else(|| {
+        text.find(|c: char| !c.is_alphanumeric() && c != '_' && c != '-')
+            .unwrap_or(text.len())
+    });
+    let (remainder, name) = take(text[..name_length].chars().count())(remainder)?;
+    let (remainder, braces) = opt(braces)(remainder)?;
+
+    if name.input.is_empty() && sigil != '~' {
+        return Err(nom::Err::Error(nom::error::ParseError::from_error_kind(
+            input,
+            nom::error::ErrorKind::Alpha,
+        )));
+    }
+
+    let name = name.input.trim().to_string();
+    let quantity = match braces {
+        Some(braces) => parse_quantity(braces, &name).map_err(nom::Err::Failure)?,
+        None => None,
+    };
+
+    let item = match sigil {
+        '@' => CooklangItem::Ingredient { name, quantity },
+        '#' => CooklangItem::Cookware { name, quantity },
+        _ => CooklangItem::Timer {
+            name: Some(name).filter(|name| !name.is_empty()),
+            quantity,
+        },
+    };
+
+    Ok((remainder, item))
+}
+
+/// The text between `{` and `}`
+fn braces(input: ParserInput) -> IResult<ParserInput, ParserInput, ParseError> {
+    let (remainder, _) = char('{')(input)?;
+    let (remainder, (contents, closed)) =
+        pair(take_till(|c| c == '}' || c == '{'), opt(char('}')))(remainder)?;
+
+    match closed {
+        Some(_) => Ok((remainder, contents)),
+        None => Err(nom::Err::Failure(ParseError::Unclosed {
+            delimiter: '{',
+            span: input.take(1).span(),
+        })),
+    }
+}
+
+/// Turn `2%cups` into a measure, using the name for counted ingredients (`@eggs{3}`)
+fn parse_quantity(braces: ParserInput, name: &str) -> Result<Option<Quantity>, ParseError> {
+    let raw = braces.input.trim();
+    if raw.is_empty() {
+        return Ok(None);
+    }
+
+    let (amount, unit) = raw.split_once('%').unwrap_or((raw, ""));
+    let (amount, unit) = (amount.trim(), unit.trim());
+    let measure = if unit.is_empty() {
+        let number = consumed(parse_number)(ParserInput::from(amount)).finish();
+        match number {
+            Ok((remainder, (_, value))) if remainder.input_len() == 0 => {
+                Some(Measure::single(value, Unit::unitless(name.to_string())))
+            }
+            Ok(_) => None,
+            Err(error) => return relocate(error, braces.span()).map(|_| None),
+        }
+    } else {
+        let measure_text = format!("{amount} {unit}");
+        let parsed =
+            parse_measure_with(&ParseOptions::default())(ParserInput::from(measure_text.as_str()))
+                .finish();
+        match parsed {
+            Ok((remainder, token)) if remainder.input_len() == 0 => Some(token.measure),
+            Ok(_) => None,
+            Err(error) => return relocate(error, braces.span()).map(|_| None),
+        }
+    };
+
+    Ok(Some(Quantity {
+        raw: raw.to_string(),
+        measure,
+    }))
+}
+
+/// Errors from parsing a quantity we copied out of the braces point into the copy, so point them
+/// back at the braces. Quantities that just aren't numbers are fine.
+fn relocate(error: ParseError, span: Span) -> Result<(), ParseError> {
+    match error {
+        ParseError::InfiniteNumber { .. } => Err(ParseError::InfiniteNumber { span }),
+        ParseError::NumberTooLarge { .. } => Err(ParseError::NumberTooLarge { span }),
+        _ => Ok(()),
+    }
+}
+
+impl Display for Cooklang {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (key, value) in &self.metadata {
+            writeln!(f, ">> {key}: {value}")?;
+        }
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 || !self.metadata.is_empty() {
+                writeln!(f)?;
+            }
+            for item in step {
+                write!(f, "{item}")?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for CooklangItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (sigil, name, quantity) = match self {
+            CooklangItem::Text(text) => return write!(f, "{text}"),
+            CooklangItem::Ingredient { name, quantity } => ('@', name.as_str(), quantity),
+            CooklangItem::Cookware { name, quantity } => ('#', name.as_str(), quantity),
+            CooklangItem::Timer { name, quantity } => {
+                ('~', name.as_deref().unwrap_or_default(), quantity)
+            }
+        };
+
+        write!(f, "{sigil}{name}")?;
+        match quantity {
+            Some(quantity) => write!(f, "{{{}}}", quantity.raw),
+            None if name.contains(' ') || name.is_empty() => write!(f, "{{}}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Quantity {
+    /// Write `measure` of the component called `name` the way Cooklang does (`1/2%cup`), leaving
+    /// the unit off counted ingredients (`@eggs{3}`)
+    pub fn new(measure: Measure, name: &str) -> Quantity {
+        let single = match measure {
+            Measure::Single(single) => single,
+            // Cooklang has no way to write `1 cup and 2 tbsp`, so add them up, keeping just the
+            // first part if that's too big for our numbers
+            Measure::Multi(mut parts) => {
+                let unit = parts[0].unit.clone();
+                let total = Magnitude::checked_from(&Measure::Multi(parts.clone()))
+                    .and_then(|total| total.checked_measure(unit));
+                match total {
+                    Some(Measure::Single(total)) => total,
+                    _ => parts.swap_remove(0),
+                }
+            }
+        };
+        let SingleMeasure { value, unit } = &single;
+
+        let raw = match unit {
+            Unit::Unitless { unit } if unit.is_empty() || unit.eq_ignore_ascii_case(name) => {
+                value.to_string()
+            }
+            Unit::Unitless { unit } => format!("{value}%{unit}"),
+            unit => format!("{value}%{}", unit.description(*value > Rational32::one())),
+        };
+
+        Quantity {
+            raw,
+            measure: Some(Measure::Single(single)),
+        }
+    }
+}
+
+impl<'a> Recipe<'a> {
+    /// Write the recipe as Cooklang, marking the ingredients each step uses along with the
+    /// amounts, and turning times into timers
+    pub fn to_cooklang(&self) -> Cooklang {
+        let structure = self.structure();
+        let links = self.link_ingredients();
+        let mut cooklang = Cooklang::default();
+        if let Some(title) = &structure.title {
+            cooklang
+                .metadata
+                .push(("title".to_string(), title.text.to_string()));
+        }
+
+        // the parts of each step we replace with a component, by the chars they cover
+        let mut components = vec![];
+        let mut mentioned = vec![false; links.ingredients.len()];
+        for (i, linked) in links.ingredients.iter().enumerate() {
+            let name = linked.ingredient.name;
+            for ingredient_use in &linked.uses {
+                mentioned[i] = true;
+                let (start, quantity) = match &ingredient_use.portion {
+                    Portion::Measure(token) => (
+                        token.full_range().start.min(ingredient_use.range.start),
+                        Some(Quantity::new(token.measure.clone(), name)),
+                    ),
+                    Portion::Fraction(fraction) => {
+                        let quantity = linked.ingredient.measure.as_ref().and_then(|token| {
+                            let magnitude = Magnitude::checked_from(&token.measure)?;
+                            let unit = token.measure.main_unit().clone();
+                            Some(Quantity::new(
+                                magnitude.checked_mul(*fraction)?.checked_measure(unit)?,
+                                name,
+                            ))
+                        });
+                        (ingredient_use.range.start, quantity)
+                    }
+                    Portion::Unspecified if linked.uses.len() == 1 => (
+                        ingredient_use.range.start,
+                        linked
+                            .ingredient
+                            .measure
+                            .as_ref()
+                            .map(|token| Quantity::new(token.measure.clone(), name)),
+                    ),
+                    Portion::Remaining | Portion::Unspecified => (ingredient_use.range.start, None),
+                };
+                let item = CooklangItem::Ingredient {
+                    name: name.to_string(),
+                    quantity,
+                };
+                components.push((start..ingredient_use.range.end, item));
+            }
+        }
+        for token in &self.tokens {
+            let range = token.full_range();
+            let is_step = structure.steps.iter().any(|step| {
+                step.body.range.start <= range.start && range.end <= step.body.range.end
+            });
+            let taken = components
+                .iter()
+                .any(|(component, _)| component.start < range.end && range.start < component.end);
+            if is_step && !taken && token.measure.dimension() == Dimension::Time {
+                let timer = CooklangItem::Timer {
+                    name: None,
+                    quantity: Some(Quantity::new(token.measure.clone(), "")),
+                };
+                components.push((range, timer));
+            }
+        }
+        components.sort_by_key(|(range, _)| range.start);
+
+        // ingredients the steps never mention still need to be somewhere
+        let unmentioned = links
+            .ingredients
+            .iter()
+            .zip(mentioned)
+            .filter(|(_, mentioned)| !mentioned)
+            .map(|(linked, _)| {
+                let name = linked.ingredient.name;
+                CooklangItem::Ingredient {
+                    name: name.to_string(),
+                    quantity: linked
+                        .ingredient
+                        .measure
+                        .as_ref()
+                        .map(|token| Quantity::new(token.measure.clone(), name)),
+                }
+            })
+            .collect::<Vec<_>>();
+        if !unmentioned.is_empty() {
+            let mut step = vec![];
+            for (i, item) in unmentioned.into_iter().enumerate() {
+                if i > 0 {
+                    step.push(CooklangItem::Text(", ".to_string()));
+                }
+                step.push(item);
+            }
+            cooklang.steps.push(step);
+        }
+
+        for step in &structure.steps {
+            cooklang.steps.push(step_items(&step.body, &components));
+        }
+
+        cooklang
+    }
+}
+
+/// Split a step into text and the components that fall inside it
+fn step_items(body: &Block, components: &[(Range<usize>, CooklangItem)]) -> Vec<CooklangItem> {
+    let text = |range: Range<usize>| {
+        let text = body
+            .text
+            .chars()
+            .skip(range.start - body.range.start)
+            .take(range.len())
+            .collect::<String>();
+        // steps that wrap onto several lines become one line
+        let mut lines = text.split('\n');
+        let first = lines.next().unwrap_or_default().to_string();
+        let text = lines.fold(first, |joined, line| {
+            format!("{} {}", joined.trim_end(), line.trim_start())
+        });
+        (!text.is_empty()).then_some(CooklangItem::Text(text))
+    };
+
+    let mut items = vec![];
+    let mut position = body.range.start;
+    for (range, item) in components {
+        if range.start < body.range.start || range.end > body.range.end {
+            continue;
+        }
+        items.extend(text(position..range.start));
+        items.push(item.clone());
+        position = range.end;
+    }
+    items.extend(text(position..body.range.end));
+
+    items
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::parse_recipe;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_cooklang() {
+        let cooklang = parse_cooklang(
+            ">> servings: 2\n\
+            Crack @eggs{3} into a #large bowl{} and add @milk{1/2%cup}. -- don't skimp\n\
+            Whisk with @salt.\n\
+            \n\
+            Cook in the #pan for ~{2%minutes}[- or so -].\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            cooklang.metadata,
+            vec![("servings".to_string(), "2".to_string())]
+        );
+        assert_eq!(cooklang.steps.len(), 2);
+        assert_eq!(
+            cooklang.steps[0][1],
+            CooklangItem::Ingredient {
+                name: "eggs".to_string(),
+                quantity: Some(Quantity {
+                    raw: "3".to_string(),
+                    measure: Some(Measure::single(
+                        Rational32::from_integer(3),
+                        Unit::unitless("eggs".to_string())
+                    ))
+                })
+            }
+        );
+        assert_eq!(
+            cooklang.steps[0][3],
+            CooklangItem::Cookware {
+                name: "large bowl".to_string(),
+                quantity: None
+            }
+        );
+        assert_eq!(
+            cooklang
+                .ingredients()
+                .map(|(name, quantity)| (name, quantity.and_then(|q| q.measure.clone())))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "eggs",
+                    Some(Measure::single(
+                        Rational32::from_integer(3),
+                        Unit::unitless("eggs".to_string())
+                    ))
+                ),
+                (
+                    "milk",
+                    Some(Measure::single(Rational32::new(1, 2), Unit::Cup))
+                ),
+                ("salt", None),
+            ]
+        );
+        assert_eq!(
+            cooklang.steps[1].last(),
+            Some(&CooklangItem::Text(".".to_string()))
+        );
+        assert_eq!(
+            cooklang.steps[1][3],
+            CooklangItem::Timer {
+                name: None,
+                quantity: Some(Quantity {
+                    raw: "2%minutes".to_string(),
+                    measure: Some(Measure::single(Rational32::from_integer(2), Unit::Minute))
+                })
+            }
+        );
+
+        assert_eq!(
+            cooklang.to_string(),
+            ">> servings: 2\n\
+            \n\
+            Crack @eggs{3} into a #large bowl{} and add @milk{1/2%cup}. Whisk with @salt.\n\
+            \n\
+            Cook in the #pan for ~{2%minutes}.\n"
+        );
+
+        assert!(matches!(
+            parse_cooklang("Add @flour{2%cups"),
+            Err(ParseError::Unclosed { delimiter: '{', .. })
+        ));
+        assert!(matches!(
+            parse_cooklang("Add @flour{1/0%cups}"),
+            Err(ParseError::InfiniteNumber { .. })
+        ));
+    }
+
+    #[test]
+    fn test_to_cooklang() {
+        let recipe = parse_recipe(
+            "Pancakes\n\
+            - 1 cup milk\n\
+            - 2 eggs\n\
+            - 1 tsp vanilla\n\
+            \n\
+            Method:\n\
+            1. Whisk the milk with 2 eggs.\n\
+            2. Rest for 10 minutes.",
+        )
+        .unwrap();
+
+        assert_eq!(
+            recipe.to_cooklang().to_string(),
+            ">> title: Pancakes\n\
+            \n\
+            @vanilla{1%teaspoon}\n\
+            \n\
+            Whisk the @milk{1%cup} with @eggs{2}.\n\
+            \n\
+            Rest for ~{10%minutes}.\n"
+        );
+
+        // half of something too big for our numbers is left without an amount
+        let recipe =
+            parse_recipe("- 100000 gallons water\n\nMethod:\n1. Add half the water.").unwrap();
+        assert_eq!(recipe.to_cooklang().to_string(), "Add half the @water.\n");
+    }
+}
@@ -1,10 +1,15 @@
-use crate::{Unit, TEMPERATURE_UNITS, TIME_UNITS, UNITLESS_UNITS, VOLUME_UNITS};
+use crate::{
+    Unit, UnitSystem, LENGTH_UNITS, MASS_UNITS, TEMPERATURE_UNITS, TIME_UNITS, UNITLESS_UNITS,
+    VOLUME_UNITS,
+};
 
 #[derive(Eq, PartialEq, Debug, Copy, Clone)]
 pub enum Dimension {
     Volume,
     Temperature,
     Time,
+    Mass,
+    Length,
     Unitless,
 }
 
@@ -14,7 +19,16 @@ impl Dimension {
             Dimension::Volume => &VOLUME_UNITS,
             Dimension::Temperature => &TEMPERATURE_UNITS,
             Dimension::Time => &TIME_UNITS,
+            Dimension::Mass => &MASS_UNITS,
+            Dimension::Length => &LENGTH_UNITS,
             Dimension::Unitless => &UNITLESS_UNITS,
         }
     }
+
+    /// The units of this dimension that can be used in `system`, smallest first
+    pub fn units_in(self, system: UnitSystem) -> impl Iterator<Item = &'static Unit> {
+        self.units()
+            .iter()
+            .filter(move |unit| unit.system().is_none_or(|s| s == system))
+    }
 }
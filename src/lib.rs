@@ -1,24 +1,93 @@
 use std::fmt::Debug;
 
+use nom::error::ErrorKind;
 use thiserror::Error;
 
+use crate::parser::Span;
+
+pub use archive::*;
+pub use cooklang::*;
 pub use dimension::*;
+pub use link::*;
 pub use magnitude::*;
+pub use mealmaster::*;
 pub use measure::*;
+pub use modifier::*;
+pub use recipeml::*;
+pub use rewrite::*;
+pub use schema_org::*;
+pub use system::*;
 pub use unit::*;
 
+mod archive;
+mod convert;
+mod cooklang;
 mod dimension;
+mod link;
+#[cfg(feature = "lsp")]
+pub mod lsp;
 mod magnitude;
+mod mealmaster;
 mod measure;
+mod modifier;
 pub mod parser;
+mod recipeml;
+mod rewrite;
+mod scale;
+mod schema_org;
+mod system;
 mod unit;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Eq, PartialEq, Clone)]
 pub enum ParseError {
-    #[error("Unknown unit: `{0}`")]
-    UnknownUnit(String),
-    #[error("Found an infinite number when parsing")]
-    InfiniteNumber,
+    #[error("unknown unit `{unit}` at {span}")]
+    UnknownUnit { unit: String, span: Span },
+    #[error("denominator is zero at {span}")]
+    InfiniteNumber { span: Span },
+    #[error("number is too large at {span}")]
+    NumberTooLarge { span: Span },
+    #[error("expected {} at {span}", expected(*kind))]
+    Unexpected { kind: ErrorKind, span: Span },
+    #[error("found unparsed text at {span}")]
+    Unconsumed { span: Span },
+    #[error("unclosed `{delimiter}` at {span}")]
+    Unclosed { delimiter: char, span: Span },
+}
+
+impl ParseError {
+    pub fn span(&self) -> &Span {
+        match self {
+            ParseError::UnknownUnit { span, .. }
+            | ParseError::InfiniteNumber { span }
+            | ParseError::NumberTooLarge { span }
+            | ParseError::Unexpected { span, .. }
+            | ParseError::Unconsumed { span }
+            | ParseError::Unclosed { span, .. } => span,
+        }
+    }
+
+    pub(crate) fn span_mut(&mut self) -> &mut Span {
+        match self {
+            ParseError::UnknownUnit { span, .. }
+            | ParseError::InfiniteNumber { span }
+            | ParseError::NumberTooLarge { span }
+            | ParseError::Unexpected { span, .. }
+            | ParseError::Unconsumed { span }
+            | ParseError::Unclosed { span, .. } => span,
+        }
+    }
+}
+
+/// Describe what the parser that failed with `kind` was looking for
+fn expected(kind: ErrorKind) -> &'static str {
+    match kind {
+        ErrorKind::Digit => "a number",
+        ErrorKind::Alpha | ErrorKind::Verify => "a unit",
+        ErrorKind::Char | ErrorKind::OneOf => "a fraction",
+        ErrorKind::Tag => "a modifier",
+        ErrorKind::Eof => "the end of the text",
+        _ => "a measure",
+    }
 }
@@ -0,0 +1,341 @@
+use num_rational::Rational32;
+use thiserror::Error;
+
+use crate::parser::{parse_ingredient_line, Block, Ingredient, MeasureToken, Recipe};
+use crate::{Magnitude, Measure, Unit};
+
+/// Words before an ingredient that say how much of it a step uses (`half the butter`)
+const PORTION_WORDS: [(&str, (i32, i32)); 6] = [
+    ("half", (1, 2)),
+    ("halves", (1, 2)),
+    ("third", (1, 3)),
+    ("thirds", (1, 3)),
+    ("quarter", (1, 4)),
+    ("quarters", (1, 4)),
+];
+/// Words before an ingredient that mean whatever wasn't used yet
+const REMAINING_WORDS: [&str; 3] = ["remaining", "rest", "reserved"];
+
+/// How many words can sit between a measure and the ingredient it measures
+/// (`1/2 cup of the sugar`)
+const MAX_GAP_WORDS: usize = 3;
+
+/// Punctuation that ends the part of a sentence an ingredient's measure can be in
+const CLAUSE_BREAKS: [char; 5] = [',', '.', ';', ':', '\n'];
+
+/// Steps can use a little more than the list says before we complain, since scaling and converting
+/// round each measure separately
+const OVERUSE_TOLERANCE: (i32, i32) = (21, 20);
+
+/// The ingredients list with every step that mentions each ingredient
+#[derive(Debug, Eq, PartialEq)]
+pub struct Links<'r, 'a> {
+    pub ingredients: Vec<LinkedIngredient<'r, 'a>>,
+    pub warnings: Vec<LinkWarning>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct LinkedIngredient<'r, 'a> {
+    pub line: Block<'r, 'a>,
+    pub ingredient: Ingredient<'a>,
+    /// The list says to split the ingredient between steps (`1 cup sugar, divided`)
+    pub divided: bool,
+    pub uses: Vec<IngredientUse<'r, 'a>>,
+}
+
+/// A step mentioning an ingredient
+#[derive(Debug, Eq, PartialEq)]
+pub struct IngredientUse<'r, 'a> {
+    /// The index of the step in [`Structure::steps`](crate::parser::Structure::steps)
+    pub step: usize,
+    /// The chars of the ingredient's name in the recipe
+    pub range: std::ops::Range<usize>,
+    pub portion: Portion<'r, 'a>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Portion<'r, 'a> {
+    /// `add 1/2 cup sugar`
+    Measure(&'r MeasureToken<'a>),
+    /// `add half the butter`
+    Fraction(Rational32),
+    /// `the remaining sugar`
+    Remaining,
+    /// `melt the butter`, which usually means all of it
+    Unspecified,
+}
+
+#[derive(Error, Debug, Eq, PartialEq, Clone)]
+pub enum LinkWarning {
+    #[error("the steps use {used:#} of {ingredient} but the list only has {listed:#}")]
+    Overused {
+        ingredient: String,
+        listed: Measure,
+        used: Measure,
+    },
+}
+
+impl<'a> Recipe<'a> {
+    /// Match the measures in each step to the ingredients they use, warning when the steps use
+    /// more of an ingredient than the list provides
+    pub fn link_ingredients(&self) -> Links<'_, 'a> {
+        let structure = self.structure();
+        let mut ingredients = structure
+            .ingredient_groups
+            .into_iter()
+            .flat_map(|group| group.lines)
+            .filter_map(|line| {
+                let ingredient = parse_ingredient_line(line.text).ok()?;
+                let divided = ingredient
+                    .notes
+                    .is_some_and(|notes| notes.to_lowercase().contains("divided"));
+                Some(LinkedIngredient {
+                    line,
+                    ingredient,
+                    divided,
+                    uses: vec![],
+                })
+            })
+            .collect::<Vec<_>>();
+        let keys = ingredients
+            .iter()
+            .map(|linked| key(linked.ingredient.name))
+            .collect::<Vec<_>>();
+
+        for (index, step) in structure.steps.iter().enumerate() {
+            let mut used_tokens = vec![];
+            for (word_start, word) in words(step.body.text) {
+                let Some(found) = keys
+                    .iter()
+                    .position(|key| key.as_deref() == Some(singular(word).as_str()))
+                else {
+                    continue;
+                };
+                let before = &step.body.text[..word_start];
+                let start = step.body.range.start + before.chars().count();
+                let range = start..start + word.chars().count();
+
+                let portion = match measure_before(step.body.tokens, start, before, &used_tokens) {
+                    Some(token) => {
+                        used_tokens.push(token.full_range());
+                        Portion::Measure(token)
+                    }
+                    None => portion_before(before),
+                };
+                ingredients[found].uses.push(IngredientUse {
+                    step: index,
+                    range,
+                    portion,
+                });
+            }
+        }
+
+        let warnings = ingredients.iter().filter_map(overused).collect();
+
+        Links {
+            ingredients,
+            warnings,
+        }
+    }
+}
+
+/// The word we look for in the steps, `flour` for `all-purpose flour`
+fn key(name: &str) -> Option<String> {
+    words(name).last().map(|(_, word)| singular(word))
+}
+
+fn singular(word: &str) -> String {
+    let word = word.to_lowercase();
+    if let Some(stem) = word.strip_suffix("ies") {
+        format!("{stem}y")
+    } else if let Some(stem) = word
+        .strip_suffix("oes")
+        .or_else(|| word.strip_suffix("shes"))
+        .or_else(|| word.strip_suffix("ches"))
+    {
+        format!("{stem}{}", &word[stem.len()..word.len() - 2])
+    } else if word.ends_with("ss") {
+        word
+    } else {
+        word.strip_suffix('s').unwrap_or(&word).to_string()
+    }
+}
+
+/// The words in `text` along with their byte offsets
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split(|c: char| !c.is_alphabetic() && c != '-')
+        .filter(|word| !word.is_empty())
+        .map(move |word| (word.as_ptr() as usize - text.as_ptr() as usize, word))
+}
+
+/// Find the measure right before an ingredient's name, as long as only a few words sit between
+/// them (`1/2 cup of the sugar`, `2 eggs`)
+fn measure_before<'r, 'a>(
+    tokens: &'r [MeasureToken<'a>],
+    name_start: usize,
+    before: &str,
+    used: &[std::ops::Range<usize>],
+) -> Option<&'r MeasureToken<'a>> {
+    let token = tokens
+        .iter()
+        .rev()
+        .find(|token| token.full_range().start <= name_start)?;
+    if used.contains(&token.full_range()) {
+        return None;
+    }
+    // counted ingredients have their name as the unit
+    if token.full_range().end > name_start {
+        return matches!(token.measure.main_unit(), Unit::Unitless { .. }).then_some(token);
+    }
+
+    let gap = before
+        .chars()
+        .skip(token.full_range().end - (name_start - before.chars().count()))
+        .collect::<String>();
+    let gap_words = gap.split_whitespace().count();
+    let clause_break = gap.contains(CLAUSE_BREAKS);
+
+    (!clause_break && gap_words <= MAX_GAP_WORDS).then_some(token)
+}
+
+/// Look for a word like `half` or `remaining` in the few words before an ingredient's name
+fn portion_before(before: &str) -> Portion<'static, 'static> {
+    let clause = before
+        .rsplit(CLAUSE_BREAKS)
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    for word in clause.split_whitespace().rev().take(MAX_GAP_WORDS) {
+        if let Some((_, (numer, denom))) =
+            PORTION_WORDS.iter().find(|(portion, _)| *portion == word)
+        {
+            return Portion::Fraction(Rational32::new(*numer, *denom));
+        }
+        if REMAINING_WORDS.contains(&word) {
+            return Portion::Remaining;
+        }
+    }
+
+    Portion::Unspecified
+}
+
+/// Add up the measured and fractional uses of an ingredient, warning if they come to more than the
+/// list has. We can't tell when the amounts are too big for our numbers, so we don't warn.
+fn overused(linked: &LinkedIngredient) -> Option<LinkWarning> {
+    let listed = linked.ingredient.measure.as_ref()?.measure.clone();
+    let listed_magnitude = Magnitude::checked_from(&listed)?;
+
+    let mut used = Magnitude::new(0, listed.main_unit().clone());
+    for ingredient_use in &linked.uses {
+        let portion = match &ingredient_use.portion {
+            Portion::Measure(token) => Magnitude::checked_from(&token.measure)?,
+            Portion::Fraction(fraction) => listed_magnitude.checked_mul(*fraction)?,
+            Portion::Remaining | Portion::Unspecified => continue,
+        };
+        used = used.checked_add(portion)?;
+    }
+
+    let allowed = listed_magnitude.checked_mul(OVERUSE_TOLERANCE.into())?;
+    (used > allowed).then_some(())?;
+    Some(LinkWarning::Overused {
+        ingredient: linked.ingredient.name.to_string(),
+        used: used.checked_measure(listed.main_unit().clone())?,
+        listed,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::parse_recipe;
+
+    use super::*;
+
+    const COOKIES: &str = "Ingredients:
+- 1 cup sugar, divided
+- 2 cups all-purpose flour
+- 4 tbsp butter
+- 2 large eggs
+
+Method:
+1. Cream half the butter with 1/2 cup sugar.
+2. Beat in 2 eggs, then the flour.
+3. Add the remaining sugar and the rest of the butter.
+";
+
+    fn portions<'l, 'r, 'a>(
+        links: &'l Links<'r, 'a>,
+        name: &str,
+    ) -> Vec<(usize, &'l Portion<'r, 'a>)> {
+        links
+            .ingredients
+            .iter()
+            .find(|linked| linked.ingredient.name == name)
+            .unwrap()
+            .uses
+            .iter()
+            .map(|ingredient_use| (ingredient_use.step, &ingredient_use.portion))
+            .collect()
+    }
+
+    #[test]
+    fn test_link_ingredients() {
+        let recipe = parse_recipe(COOKIES).unwrap();
+        let links = recipe.link_ingredients();
+
+        assert!(links.ingredients[0].divided);
+        assert!(!links.ingredients[1].divided);
+
+        let sugar = portions(&links, "sugar");
+        assert_eq!(sugar.len(), 2);
+        assert!(matches!(
+            sugar[0],
+            (0, Portion::Measure(token)) if token.measure.main_unit() == &Unit::Cup
+        ));
+        assert_eq!(sugar[1], (2, &Portion::Remaining));
+
+        assert_eq!(
+            portions(&links, "butter"),
+            vec![
+                (0, &Portion::Fraction(Rational32::new(1, 2))),
+                (2, &Portion::Remaining)
+            ]
+        );
+        assert!(matches!(
+            portions(&links, "eggs")[..],
+            [(1, Portion::Measure(_))]
+        ));
+        assert_eq!(
+            portions(&links, "all-purpose flour"),
+            vec![(1, &Portion::Unspecified)]
+        );
+        assert_eq!(links.warnings, vec![]);
+
+        // the steps stay in line with the list once we scale both
+        let scaled = recipe.scale(3).text;
+        let scaled = parse_recipe(scaled.as_str()).unwrap();
+        assert_eq!(scaled.link_ingredients().warnings, vec![]);
+    }
+
+    #[test]
+    fn test_overused() {
+        let recipe = parse_recipe(
+            "- 1 cup milk\n- 3 eggs\n\nSteps:\n- Whisk 3/4 cup milk with 2 eggs\n\
+            - Stir in another 1/2 cup of milk and 1 egg",
+        )
+        .unwrap();
+        let warnings = recipe.link_ingredients().warnings;
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].to_string(),
+            "the steps use 1 1/4 cups of milk but the list only has 1 cup"
+        );
+
+        // amounts too big to add up aren't compared
+        let recipe =
+            parse_recipe("Ingredients\n- 100000 gallons water\n\n1. Add 100000 gallons water")
+                .unwrap();
+        assert!(recipe.link_ingredients().warnings.is_empty());
+    }
+}
@@ -0,0 +1,666 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as LspNotification, PublishDiagnostics,
+};
+use lsp_types::request::{
+    CodeActionRequest, HoverRequest, InlayHintRequest, Request as LspRequest,
+};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams,
+    CodeActionProviderCapability, Diagnostic, DiagnosticSeverity, Hover, HoverContents,
+    HoverParams, HoverProviderCapability, InlayHint, InlayHintLabel, InlayHintParams,
+    MarkupContent, MarkupKind, OneOf, PublishDiagnosticsParams, ServerCapabilities,
+    TextDocumentSyncCapability, TextDocumentSyncKind, Uri, WorkspaceEdit,
+};
+use num_rational::Rational32;
+
+use crate::convert::convert_measure;
+use crate::parser::{
+    parse_recipe, Confidence, MeasureToken, Position, PositionCursor, Recipe, TextOffset,
+    COMMON_COUNT_NOUNS,
+};
+use crate::rewrite::render_measure;
+use crate::scale::scale_measure;
+use crate::{Dimension, Magnitude, Unit, UnitSystem};
+
+/// Where our diagnostics say they came from
+const SOURCE: &str = "recipe-measures";
+
+/// Serve the language server protocol over `connection` until the client shuts us down
+pub fn run(connection: Connection) -> Result<(), Box<dyn Error + Sync + Send>> {
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(
+            TextDocumentSyncKind::INCREMENTAL,
+        )),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        inlay_hint_provider: Some(OneOf::Left(true)),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        ..Default::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    let mut server = Server::default();
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                connection
+                    .sender
+                    .send(Message::Response(server.request(request)))?;
+            }
+            Message::Notification(notification) => {
+                if let Some(uri) = server.notification(notification) {
+                    let params = PublishDiagnosticsParams {
+                        diagnostics: server.diagnostics(&uri),
+                        uri,
+                        version: None,
+                    };
+                    connection
+                        .sender
+                        .send(Message::Notification(Notification::new(
+                            PublishDiagnostics::METHOD.to_string(),
+                            params,
+                        )))?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// The text of every document the client has open
+#[derive(Default)]
+struct Server {
+    documents: HashMap<Uri, String>,
+}
+
+impl Server {
+    fn request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            HoverRequest::METHOD => self.respond::<HoverRequest>(request, Server::hover),
+            InlayHintRequest::METHOD => {
+                self.respond::<InlayHintRequest>(request, Server::inlay_hints)
+            }
+            CodeActionRequest::METHOD => {
+                self.respond::<CodeActionRequest>(request, Server::code_actions)
+            }
+            method => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("unsupported request `{method}`"),
+            ),
+        }
+    }
+
+    fn respond<R: LspRequest>(
+        &self,
+        request: Request,
+        handler: impl Fn(&Server, R::Params) -> R::Result,
+    ) -> Response {
+        match serde_json::from_value(request.params) {
+            Ok(params) => Response::new_ok(request.id, handler(self, params)),
+            Err(error) => Response::new_err(
+                request.id,
+                ErrorCode::InvalidParams as i32,
+                error.to_string(),
+            ),
+        }
+    }
+
+    /// Keep track of the open documents, returning the one that changed
+    fn notification(&mut self, notification: Notification) -> Option<Uri> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params = notification
+                    .extract::<lsp_types::DidOpenTextDocumentParams>(DidOpenTextDocument::METHOD);
+                let document = params.ok()?.text_document;
+                self.documents.insert(document.uri.clone(), document.text);
+
+                Some(document.uri)
+            }
+            DidChangeTextDocument::METHOD => {
+                let params = notification
+                    .extract::<lsp_types::DidChangeTextDocumentParams>(
+                        DidChangeTextDocument::METHOD,
+                    )
+                    .ok()?;
+                let text = self.documents.get_mut(&params.text_document.uri)?;
+                for change in params.content_changes {
+                    match change.range {
+                        Some(range) => {
+                            // a client sending the range backwards shouldn't bring us down
+                            let start = byte_offset(text, range.start);
+                            let end = byte_offset(text, range.end);
+                            text.replace_range(start.min(end)..start.max(end), &change.text);
+                        }
+                        None => *text = change.text,
+                    }
+                }
+
+                Some(params.text_document.uri)
+            }
+            DidCloseTextDocument::METHOD => {
+                let params = notification
+                    .extract::<lsp_types::DidCloseTextDocumentParams>(DidCloseTextDocument::METHOD)
+                    .ok()?;
+                self.documents.remove(&params.text_document.uri);
+
+                // clear the document's diagnostics
+                Some(params.text_document.uri)
+            }
+            _ => None,
+        }
+    }
+
+    fn recipe(&self, uri: &Uri) -> Option<Recipe<'_>> {
+        parse_recipe(self.documents.get(uri)?.as_str()).ok()
+    }
+
+    fn diagnostics(&self, uri: &Uri) -> Vec<Diagnostic> {
+        let Some(recipe) = self.recipe(uri) else {
+            return vec![];
+        };
+
+        let mut positions = PositionCursor::new(recipe.raw);
+        let mut diagnostics = recipe
+            .warnings
+            .iter()
+            .map(|warning| {
+                let bytes = &warning.span().bytes;
+                let range = positions.at(bytes.start)..positions.at(bytes.end);
+                diagnostic(range, DiagnosticSeverity::ERROR, warning.to_string())
+            })
+            .collect::<Vec<_>>();
+
+        for token in &recipe.tokens {
+            let unit = token.measure.main_unit();
+            if !token.alternatives.is_empty() {
+                let severity = match token.confidence {
+                    Confidence::Guess => DiagnosticSeverity::WARNING,
+                    _ => DiagnosticSeverity::INFORMATION,
+                };
+                let others = token
+                    .alternatives
+                    .iter()
+                    .map(|alternative| alternative.description(true))
+                    .collect::<Vec<_>>()
+                    .join(" or ");
+                let message = format!(
+                    "`{}` could also mean {others}, reading it as {}",
+                    token.unit_text(),
+                    unit.description(true)
+                );
+                diagnostics.push(diagnostic(token.unit_positions(), severity, message));
+            } else if let Unit::Unitless { unit } = unit {
+                let counted = COMMON_COUNT_NOUNS
+                    .iter()
+                    .any(|noun| noun.eq_ignore_ascii_case(unit));
+                if !counted {
+                    let message = format!("unknown unit `{unit}`");
+                    diagnostics.push(diagnostic(
+                        token.unit_positions(),
+                        DiagnosticSeverity::HINT,
+                        message,
+                    ));
+                }
+            }
+        }
+        diagnostics.sort_by_key(|diagnostic| diagnostic.range.start);
+
+        diagnostics
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let position = params.text_document_position_params;
+        let recipe = self.recipe(&position.text_document.uri)?;
+        let token = recipe.token_at(text_offset(position.position))?;
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: describe(token),
+            }),
+            range: Some(lsp_range(token.positions())),
+        })
+    }
+
+    fn inlay_hints(&self, params: InlayHintParams) -> Option<Vec<InlayHint>> {
+        let recipe = self.recipe(&params.text_document.uri)?;
+        let range = text_offset(params.range.start)..text_offset(params.range.end);
+
+        Some(
+            recipe
+                .tokens_in(range)
+                .iter()
+                .filter_map(|token| {
+                    let metric = convert_measure(&token.measure, UnitSystem::Metric)?;
+                    Some(InlayHint {
+                        position: lsp_position(token.positions().end),
+                        label: InlayHintLabel::String(format!("({metric})")),
+                        kind: None,
+                        text_edits: None,
+                        tooltip: None,
+                        padding_left: Some(true),
+                        padding_right: None,
+                        data: None,
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    fn code_actions(&self, params: CodeActionParams) -> Option<Vec<CodeActionOrCommand>> {
+        let uri = params.text_document.uri;
+        let recipe = self.recipe(&uri)?;
+        let range = text_offset(params.range.start)..text_offset(params.range.end);
+        let tokens = if params.range.start == params.range.end {
+            recipe.token_at(range.start).map(std::slice::from_ref)?
+        } else {
+            recipe.tokens_in(range)
+        };
+
+        let mut actions = vec![];
+        for token in tokens {
+            let conversions = [UnitSystem::Metric, UnitSystem::UsCustomary]
+                .into_iter()
+                .filter_map(|system| convert_measure(&token.measure, system))
+                .map(|measure| (format!("Convert to {measure:#}"), measure));
+            let scaled = [
+                ("Double", Rational32::from_integer(2)),
+                ("Halve", Rational32::new(1, 2)),
+            ]
+            .into_iter()
+            .filter_map(|(verb, factor)| {
+                let measure = scale_measure(&token.measure, factor)?;
+                Some((format!("{verb} to {measure:#}"), measure))
+            });
+
+            for (title, measure) in conversions.chain(scaled) {
+                let edit = lsp_types::TextEdit {
+                    range: lsp_range(token.positions()),
+                    new_text: render_measure(token, &measure),
+                };
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title,
+                    kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        Some(actions)
+    }
+}
+
+/// What we show when hovering over a measure: what it is in both systems and other ways of
+/// writing it
+fn describe(token: &MeasureToken) -> String {
+    let measure = &token.measure;
+    let mut lines = vec![format!("**{measure:#}**")];
+    for (name, system) in [
+        ("Metric", UnitSystem::Metric),
+        ("US customary", UnitSystem::UsCustomary),
+    ] {
+        if let Some(converted) = convert_measure(measure, system) {
+            lines.push(format!("{name}: {converted:#}"));
+        }
+    }
+
+    // measures too big for our numbers only get described as they're written
+    let magnitude = Magnitude::checked_from(measure)
+        .filter(|magnitude| magnitude.dimension() != Dimension::Unitless);
+    if let Some(magnitude) = magnitude {
+        let system = measure.main_unit().system().unwrap_or_default();
+        let others = magnitude
+            .best_measures_in(system)
+            .into_iter()
+            .filter(|best| best != measure)
+            .map(|best| format!("{best:#}"))
+            .collect::<Vec<_>>();
+        if !others.is_empty() {
+            lines.push(format!("Also: {}", others.join(", ")));
+        }
+    }
+
+    if !token.alternatives.is_empty() {
+        let others = token
+            .alternatives
+            .iter()
+            .map(|alternative| alternative.description(true))
+            .collect::<Vec<_>>();
+        lines.push(format!("Could also be {}", others.join(" or ")));
+    }
+
+    lines.join("\n\n")
+}
+
+fn diagnostic(
+    range: std::ops::Range<Position>,
+    severity: DiagnosticSeverity,
+    message: String,
+) -> Diagnostic {
+    Diagnostic {
+        range: lsp_range(range),
+        severity: Some(severity),
+        source: Some(SOURCE.to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+fn text_offset(position: lsp_types::Position) -> TextOffset {
+    TextOffset::LineUtf16 {
+        line: position.line as usize,
+        utf16_column: position.character as usize,
+    }
+}
+
+fn lsp_position(position: Position) -> lsp_types::Position {
+    lsp_types::Position::new(position.line as u32, position.utf16_column as u32)
+}
+
+fn lsp_range(range: std::ops::Range<Position>) -> lsp_types::Range {
+    lsp_types::Range::new(lsp_position(range.start), lsp_position(range.end))
+}
+
+/// The byte of `text` a client's position points at, clamped to the line it's on
+fn byte_offset(text: &str, position: lsp_types::Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match text[line_start..].find('\n') {
+            Some(end) => line_start += end + 1,
+            None => return text.len(),
+        }
+    }
+
+    let mut utf16 = 0;
+    for (i, c) in text[line_start..].char_indices() {
+        if utf16 >= position.character as usize || c == '\n' {
+            return line_start + i;
+        }
+        utf16 += c.len_utf16();
+    }
+
+    text.len()
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread::{self, JoinHandle};
+
+    use lsp_server::RequestId;
+    use lsp_types::notification::{Exit, Initialized};
+    use lsp_types::request::{Initialize, Shutdown};
+    use lsp_types::{
+        DidChangeTextDocumentParams, DidOpenTextDocumentParams, TextDocumentContentChangeEvent,
+        TextDocumentIdentifier, TextDocumentItem, TextDocumentPositionParams,
+        VersionedTextDocumentIdentifier,
+    };
+    use serde_json::json;
+
+    use super::*;
+
+    /// A client talking to the server in memory
+    struct Client {
+        connection: Connection,
+        server: JoinHandle<()>,
+        next_id: i32,
+    }
+
+    impl Client {
+        fn start() -> Client {
+            let (server, connection) = Connection::memory();
+            let server = thread::spawn(move || run(server).unwrap());
+            let mut client = Client {
+                connection,
+                server,
+                next_id: 0,
+            };
+            client.send::<Initialize>(json!({ "capabilities": {} }));
+            client.notify::<Initialized>(json!({}));
+
+            client
+        }
+
+        fn send<R: LspRequest>(&mut self, params: impl serde::Serialize) -> serde_json::Value {
+            self.next_id += 1;
+            let id = RequestId::from(self.next_id);
+            let request = Request::new(id.clone(), R::METHOD.to_string(), params);
+            self.connection.sender.send(request.into()).unwrap();
+            loop {
+                match self.connection.receiver.recv().unwrap() {
+                    Message::Response(response) if response.id == id => {
+                        return response.result.unwrap_or_default()
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        fn request<R: LspRequest>(&mut self, params: R::Params) -> R::Result {
+            serde_json::from_value(self.send::<R>(params)).unwrap()
+        }
+
+        fn notify<N: LspNotification>(&self, params: impl serde::Serialize) {
+            let notification = Notification::new(N::METHOD.to_string(), params);
+            self.connection.sender.send(notification.into()).unwrap();
+        }
+
+        fn diagnostics(&self) -> Vec<Diagnostic> {
+            loop {
+                if let Message::Notification(notification) =
+                    self.connection.receiver.recv().unwrap()
+                {
+                    let params = notification
+                        .extract::<PublishDiagnosticsParams>(PublishDiagnostics::METHOD)
+                        .unwrap();
+                    return params.diagnostics;
+                }
+            }
+        }
+
+        fn shut_down(mut self) {
+            self.send::<Shutdown>(());
+            self.notify::<Exit>(());
+            self.server.join().unwrap();
+        }
+    }
+
+    fn open(client: &Client, text: &str) -> Uri {
+        let uri = "file:///cookies.txt".parse::<Uri>().unwrap();
+        client.notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(uri.clone(), "text".into(), 1, text.into()),
+        });
+
+        uri
+    }
+
+    #[test]
+    fn test_hover_and_diagnostics() {
+        let mut client = Client::start();
+        let uri = open(
+            &client,
+            "🥚 Cookies\nMix 2 cups flour, 1/0 tsp salt and 3 pinchs love.\nBake at 350 C",
+        );
+
+        let diagnostics = client
+            .diagnostics()
+            .into_iter()
+            .map(|diagnostic| (diagnostic.range.start.line, diagnostic.severity.unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            vec![
+                (1, DiagnosticSeverity::ERROR),
+                (1, DiagnosticSeverity::HINT),
+                (2, DiagnosticSeverity::INFORMATION)
+            ]
+        );
+
+        let hover = client
+            .request::<HoverRequest>(HoverParams {
+                text_document_position_params: TextDocumentPositionParams::new(
+                    TextDocumentIdentifier::new(uri.clone()),
+                    lsp_types::Position::new(1, 6),
+                ),
+                work_done_progress_params: Default::default(),
+            })
+            .unwrap();
+        let HoverContents::Markup(contents) = hover.contents else {
+            panic!("expected markdown");
+        };
+        assert!(contents.value.starts_with("**2 cups**"));
+        assert!(contents.value.contains("Metric: 480 milliliters"));
+        assert_eq!(
+            hover.range,
+            Some(lsp_types::Range::new(
+                lsp_types::Position::new(1, 4),
+                lsp_types::Position::new(1, 10)
+            ))
+        );
+
+        // editing the document updates what we know about it
+        let change = |version, from, to, text: &str| DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier::new(uri.clone(), version),
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: Some(lsp_types::Range::new(
+                    lsp_types::Position::new(1, from),
+                    lsp_types::Position::new(1, to),
+                )),
+                range_length: None,
+                text: text.into(),
+            }],
+        };
+        client.notify::<DidChangeTextDocument>(change(2, 18, 21, "1/2"));
+        assert_eq!(client.diagnostics().len(), 2);
+        client.notify::<DidChangeTextDocument>(change(3, 21, 18, "1/0"));
+        assert_eq!(client.diagnostics().len(), 3);
+
+        client.shut_down();
+    }
+
+    #[test]
+    fn test_inlay_hints_and_code_actions() {
+        let mut client = Client::start();
+        let uri = open(&client, "Mix 2 cups flour with 250 g butter\nBake at 350°F");
+        client.diagnostics();
+
+        let whole = lsp_types::Range::new(
+            lsp_types::Position::new(0, 0),
+            lsp_types::Position::new(2, 0),
+        );
+        let hints = client
+            .request::<InlayHintRequest>(InlayHintParams {
+                work_done_progress_params: Default::default(),
+                text_document: TextDocumentIdentifier::new(uri.clone()),
+                range: whole,
+            })
+            .unwrap()
+            .into_iter()
+            .map(|hint| match hint.label {
+                InlayHintLabel::String(label) => (hint.position, label),
+                InlayHintLabel::LabelParts(_) => panic!("expected a plain label"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            hints,
+            vec![
+                (lsp_types::Position::new(0, 10), "(480 ml)".to_string()),
+                (lsp_types::Position::new(1, 13), "(175 C)".to_string())
+            ]
+        );
+
+        let cursor = lsp_types::Position::new(0, 5);
+        let actions = client
+            .request::<CodeActionRequest>(CodeActionParams {
+                text_document: TextDocumentIdentifier::new(uri.clone()),
+                range: lsp_types::Range::new(cursor, cursor),
+                context: Default::default(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap();
+        let edits = actions
+            .into_iter()
+            .map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => {
+                    let edits = action.edit.unwrap().changes.unwrap().into_values();
+                    (action.title, edits.flatten().next().unwrap().new_text)
+                }
+                CodeActionOrCommand::Command(_) => panic!("expected an edit"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            edits,
+            vec![
+                (
+                    "Convert to 480 milliliters".to_string(),
+                    "480 milliliters".to_string()
+                ),
+                ("Double to 4 cups".to_string(), "4 cups".to_string()),
+                ("Halve to 1 cup".to_string(), "1 cup".to_string()),
+            ]
+        );
+
+        client.shut_down();
+    }
+
+    #[test]
+    fn test_oversized_measures() {
+        let mut client = Client::start();
+        let uri = open(
+            &client,
+            "Fill with 100000 gallons water\nAdd 2000000000 ml milk",
+        );
+        client.diagnostics();
+        let document = TextDocumentIdentifier::new(uri);
+        let cursor = lsp_types::Position::new(0, 12);
+
+        let hover = client
+            .request::<HoverRequest>(HoverParams {
+                text_document_position_params: TextDocumentPositionParams::new(
+                    document.clone(),
+                    cursor,
+                ),
+                work_done_progress_params: Default::default(),
+            })
+            .unwrap();
+        let HoverContents::Markup(contents) = hover.contents else {
+            panic!("expected markdown");
+        };
+        assert_eq!(contents.value, "**100000 gallons**");
+
+        let hints = client.request::<InlayHintRequest>(InlayHintParams {
+            work_done_progress_params: Default::default(),
+            text_document: document.clone(),
+            range: lsp_types::Range::new(
+                lsp_types::Position::new(0, 0),
+                lsp_types::Position::new(2, 0),
+            ),
+        });
+        assert!(hints.unwrap().is_empty());
+
+        let actions = client.request::<CodeActionRequest>(CodeActionParams {
+            text_document: document,
+            range: lsp_types::Range::new(cursor, cursor),
+            context: Default::default(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+        assert_eq!(actions, Some(vec![]));
+
+        client.shut_down();
+    }
+}
@@ -1,7 +1,9 @@
 use num_rational::Rational32;
+use num_traits::{CheckedAdd, CheckedMul, Zero};
+use std::cmp::Ordering;
 use std::ops;
 
-use crate::{Dimension, Measure, SingleMeasure, Unit};
+use crate::{Dimension, Measure, SingleMeasure, Unit, UnitSystem};
 
 #[derive(Eq, PartialEq, Debug, Copy, Clone)]
 pub struct Magnitude {
@@ -12,11 +14,33 @@ pub struct Magnitude {
 impl Magnitude {
     pub fn new<R: Into<Rational32>>(value: R, unit: Unit) -> Magnitude {
         Magnitude {
-            base_value: value.into() * unit.multiple(),
+            base_value: unit.base_value(value.into()),
             dimension: unit.dimension(),
         }
     }
 
+    /// The magnitude of `measure`, or [`None`] if it's too big to be written in the base unit of
+    /// its dimension
+    pub fn checked_from(measure: &Measure) -> Option<Magnitude> {
+        let base_value = match measure {
+            Measure::Single(single) => single.checked_base()?,
+            Measure::Multi(measures) => {
+                measures.iter().try_fold(Rational32::zero(), |sum, m| {
+                    sum.checked_add(&m.checked_base()?)
+                })?
+            }
+        };
+
+        Some(Magnitude {
+            base_value,
+            dimension: measure.dimension(),
+        })
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.dimension
+    }
+
     // fn unitless(value: Rational32) -> Magnitude {
     //     Magnitude {
     //         base_value: value,
@@ -24,17 +48,48 @@ impl Magnitude {
     //     }
     // }
 
+    /// Multiply by `multiple`, returning [`None`] if the result doesn't fit in our numbers
+    pub fn checked_mul(self, multiple: Rational32) -> Option<Magnitude> {
+        Some(Self {
+            base_value: self.base_value.checked_mul(&multiple)?,
+            dimension: self.dimension,
+        })
+    }
+
+    /// Add `other`, returning [`None`] if it's a different dimension or the result doesn't fit in
+    /// our numbers
+    pub fn checked_add(self, other: Magnitude) -> Option<Magnitude> {
+        (self.dimension == other.dimension).then_some(())?;
+        Some(Self {
+            base_value: self.base_value.checked_add(&other.base_value)?,
+            dimension: self.dimension,
+        })
+    }
+
     pub fn measure(self, unit: Unit) -> Measure {
         Measure::from_base(self.base_value, unit)
     }
 
+    /// [`Magnitude::measure`], or [`None`] if the value in `unit` doesn't fit in our numbers
+    pub fn checked_measure(self, unit: Unit) -> Option<Measure> {
+        Measure::checked_from_base(self.base_value, unit)
+    }
+
     pub fn best_measures(self) -> Vec<Measure> {
+        self.best_measures_in(UnitSystem::UsCustomary)
+    }
+
+    pub fn best_measures_in(self, system: UnitSystem) -> Vec<Measure> {
         // const COMMON_FRACTIONS: [Rational32; 2] =
         //     [Rational32::new_raw(1, 8), Rational32::new_raw(1, 3)];
-        let units = self.dimension.units();
+        let units = self.dimension.units_in(system).cloned().collect::<Vec<_>>();
         let mut quantities = vec![];
         'unit_loop: for (i, unit) in units.iter().cloned().enumerate() {
-            let quantity = SingleMeasure::from_base(self.base_value, unit.clone());
+            // units too small to hold the value are left out
+            let Some(quantity) = SingleMeasure::checked_from_base(self.base_value, unit.clone())
+            else {
+                continue;
+            };
 
             if quantity.is_good() {
                 quantities.push(quantity.into());
@@ -42,8 +97,11 @@ impl Magnitude {
                 for j in (0..i).rev() {
                     let main_quantity =
                         SingleMeasure::from_base(quantity.base_trunc(), unit.clone());
-                    let sub_quantity =
-                        SingleMeasure::from_base(quantity.base_fract(), units[j].clone());
+                    let Some(sub_quantity) =
+                        SingleMeasure::checked_from_base(quantity.base_fract(), units[j].clone())
+                    else {
+                        continue;
+                    };
 
                     if main_quantity.is_good() && sub_quantity.is_good() {
                         quantities.push(Measure::Multi(vec![main_quantity, sub_quantity]));
@@ -66,7 +124,11 @@ impl Magnitude {
     }
 
     pub fn best_measure(self) -> Option<Measure> {
-        let mut measures = self.best_measures().into_iter();
+        self.best_measure_in(UnitSystem::UsCustomary)
+    }
+
+    pub fn best_measure_in(self, system: UnitSystem) -> Option<Measure> {
+        let mut measures = self.best_measures_in(system).into_iter();
         let fallback = measures.next();
         for measure in measures.rev() {
             if measure.main_unit().is_common() {
@@ -89,6 +151,13 @@ impl ops::Mul<Rational32> for Magnitude {
     }
 }
 
+/// Magnitudes of different dimensions can't be compared
+impl PartialOrd for Magnitude {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (self.dimension == other.dimension).then(|| self.base_value.cmp(&other.base_value))
+    }
+}
+
 impl From<Measure> for Magnitude {
     fn from(value: Measure) -> Self {
         let base_value = match &value {
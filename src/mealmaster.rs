@@ -0,0 +1,370 @@
+use std::fmt::Write;
+
+use crate::archive::{code_for_unit, is_unit_code, yield_parts};
+use crate::schema_org::parse_yield_text;
+use crate::{ArchivedGroup, ArchivedIngredient, ArchivedRecipe, Fraction};
+
+const HEADER: &str = "MMMMM----- Recipe via Meal-Master (tm) v8.05";
+/// How wide the writer lets directions get
+const LINE_WIDTH: usize = 70;
+/// Where the second column starts in two column ingredient lists
+const SECOND_COLUMN: usize = 41;
+
+/// Read every recipe in a MealMaster file. Anything outside a recipe's header and end marker is
+/// ignored, so messages and other junk between recipes don't matter.
+pub fn read_mealmaster(text: &str) -> Vec<ArchivedRecipe> {
+    let mut recipes = vec![];
+    let mut reader: Option<Reader> = None;
+    for line in text.lines() {
+        let line = line.trim_end();
+        if is_header(line) {
+            recipes.extend(reader.take().map(Reader::finish));
+            reader = Some(Reader::default());
+        } else if line == "MMMMM" || line == "-----" {
+            recipes.extend(reader.take().map(Reader::finish));
+        } else if let Some(current) = reader.as_mut() {
+            current.line(line);
+        }
+    }
+    recipes.extend(reader.map(Reader::finish));
+
+    recipes
+}
+
+/// Write recipes out as a MealMaster file, one ingredient per line
+pub fn write_mealmaster(recipes: &[ArchivedRecipe]) -> String {
+    let mut output = String::new();
+    for (i, recipe) in recipes.iter().enumerate() {
+        if i > 0 {
+            output.push('\n');
+        }
+        write_recipe(&mut output, recipe).unwrap();
+    }
+
+    output
+}
+
+fn write_recipe(output: &mut String, recipe: &ArchivedRecipe) -> std::fmt::Result {
+    writeln!(output, "{HEADER}\n")?;
+    writeln!(output, "{:>12} {}", "Title:", recipe.title)?;
+    writeln!(
+        output,
+        "{:>12} {}",
+        "Categories:",
+        recipe.categories.join(", ")
+    )?;
+    match recipe.yields.as_ref().and_then(yield_parts) {
+        Some((servings, "servings")) => {
+            writeln!(output, "{:>12} {}", "Servings:", Fraction(servings))?
+        }
+        Some((amount, noun)) => writeln!(output, "{:>12} {} {noun}", "Yield:", Fraction(amount))?,
+        None => {}
+    }
+    output.push('\n');
+
+    for group in &recipe.ingredient_groups {
+        if let Some(heading) = &group.heading {
+            let dashes = 65usize.saturating_sub(heading.chars().count());
+            let left = dashes / 2;
+            writeln!(
+                output,
+                "MMMMM{}{heading}{}",
+                "-".repeat(left),
+                "-".repeat(dashes - left)
+            )?;
+        }
+        for ingredient in &group.ingredients {
+            let line = ingredient_line(ingredient);
+            writeln!(output, "{}", line.trim_end())?;
+        }
+    }
+    output.push('\n');
+
+    for direction in &recipe.directions {
+        for line in wrap(direction, LINE_WIDTH - 2) {
+            writeln!(output, "  {line}")?;
+        }
+        output.push('\n');
+    }
+    writeln!(output, "MMMMM")
+}
+
+/// Lay an ingredient out in MealMaster's columns, moving units that don't have a code into the text
+fn ingredient_line(ingredient: &ArchivedIngredient) -> String {
+    let code = if is_unit_code(&ingredient.unit) {
+        Some(ingredient.unit.as_str())
+    } else {
+        ingredient
+            .measure
+            .as_ref()
+            .and_then(|measure| code_for_unit(measure.main_unit()))
+    };
+    let text = match code {
+        Some(_) => ingredient.text(),
+        None => format!("{} {}", ingredient.unit, ingredient.text()),
+    };
+
+    format!(
+        "{:>7} {:<2} {}",
+        ingredient.quantity,
+        code.unwrap_or_default(),
+        text.trim()
+    )
+}
+
+/// Break a paragraph into lines no wider than `width`, unless a single word is
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+
+    lines
+}
+
+fn is_header(line: &str) -> bool {
+    (line.starts_with("MMMMM-----") || line.starts_with("----------"))
+        && (line.contains("Meal-Master") || line.contains("Recipe via"))
+}
+
+/// The heading in a line like `MMMMM-------FOR THE GLAZE-------`
+fn group_heading(line: &str) -> Option<&str> {
+    let rest = line
+        .strip_prefix("MMMMM")
+        .or_else(|| line.strip_prefix("-----"))?;
+    let heading = rest.trim_matches('-').trim();
+
+    (rest.starts_with('-') && !heading.is_empty()).then_some(heading)
+}
+
+/// Split an ingredient line into its quantity, unit and text columns
+fn ingredient_fields(line: &str) -> Option<(&str, &str, &str)> {
+    if !line.get(..11)?.is_ascii() {
+        return None;
+    }
+    let (quantity, unit, text) = (&line[..7], &line[7..11], line[11..].trim_end());
+
+    let quantity_ok = quantity.trim().is_empty()
+        || (!quantity.ends_with(' ')
+            && quantity
+                .chars()
+                .all(|c| c.is_ascii_digit() || " /.-".contains(c)));
+    let unit_ok = unit.starts_with(' ') && unit.ends_with(' ') && is_unit_code(unit.trim());
+    let text_ok = !text.is_empty() && !text.starts_with(' ');
+
+    (quantity_ok && unit_ok && text_ok).then(|| (quantity.trim(), unit.trim(), text))
+}
+
+/// The quantity, unit and text of an ingredient line
+type Columns = (String, String, String);
+
+#[derive(Default)]
+struct Reader {
+    recipe: ArchivedRecipe,
+    /// Each ingredient's columns, since a continuation line can add to the text
+    groups: Vec<(Option<String>, Vec<Columns>)>,
+    in_directions: bool,
+    paragraph: Vec<String>,
+}
+
+impl Reader {
+    fn line(&mut self, line: &str) {
+        if let Some(heading) = group_heading(line) {
+            if self.in_directions {
+                self.end_paragraph();
+            } else {
+                self.groups.push((Some(heading.to_string()), vec![]));
+            }
+            return;
+        }
+
+        if !self.in_directions {
+            if self.groups.is_empty() && self.header_field(line) {
+                return;
+            }
+            if line.is_empty() {
+                return;
+            }
+            let columns = line
+                .get(..SECOND_COLUMN - 2)
+                .zip(line.get(SECOND_COLUMN..))
+                .and_then(|(left, right)| {
+                    Some([ingredient_fields(left)?, ingredient_fields(right)?])
+                });
+            match columns {
+                Some(columns) => columns
+                    .into_iter()
+                    .for_each(|fields| self.ingredient(fields)),
+                None => match ingredient_fields(line) {
+                    Some(fields) => self.ingredient(fields),
+                    None => self.in_directions = true,
+                },
+            }
+            if !self.in_directions {
+                return;
+            }
+        }
+
+        match line.trim() {
+            "" => self.end_paragraph(),
+            text => self.paragraph.push(text.to_string()),
+        }
+    }
+
+    /// Read a `Title:`, `Categories:` or `Yield:` line, returning whether it was one
+    fn header_field(&mut self, line: &str) -> bool {
+        let Some((field, value)) = line.split_once(':') else {
+            return false;
+        };
+        let value = value.trim();
+        match field.trim() {
+            "Title" => self.recipe.title = value.to_string(),
+            "Categories" => {
+                self.recipe.categories = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|category| !category.is_empty())
+                    .map(ToString::to_string)
+                    .collect()
+            }
+            "Yield" | "Servings" => self.recipe.yields = parse_yield_text(value),
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn ingredient(&mut self, (quantity, unit, text): (&str, &str, &str)) {
+        if self.groups.is_empty() {
+            self.groups.push((None, vec![]));
+        }
+        let (_, ingredients) = self.groups.last_mut().unwrap();
+
+        // a line starting with `-` carries on the text of the one before it
+        match (text.strip_prefix('-'), ingredients.last_mut()) {
+            (Some(rest), Some((_, _, previous))) if quantity.is_empty() && unit.is_empty() => {
+                previous.push(' ');
+                previous.push_str(rest.trim());
+            }
+            _ => ingredients.push((quantity.to_string(), unit.to_string(), text.to_string())),
+        }
+    }
+
+    fn end_paragraph(&mut self) {
+        if !self.paragraph.is_empty() {
+            self.recipe.directions.push(self.paragraph.join(" "));
+            self.paragraph.clear();
+        }
+    }
+
+    fn finish(mut self) -> ArchivedRecipe {
+        self.end_paragraph();
+        self.recipe.ingredient_groups = self
+            .groups
+            .into_iter()
+            .map(|(heading, ingredients)| ArchivedGroup {
+                heading,
+                ingredients: ingredients
+                    .iter()
+                    .map(|(quantity, unit, text)| {
+                        let (name, notes) = match text.split_once(", ") {
+                            Some((name, notes)) => (name, Some(notes)),
+                            None => (text.as_str(), None),
+                        };
+                        ArchivedIngredient::new(quantity, unit, name, notes)
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        self.recipe
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use num_rational::Rational32;
+
+    use crate::{Measure, Modifier, Unit};
+
+    use super::*;
+
+    const COOKIES: &str = include_str!("../test_data/cookies.mmf");
+
+    #[test]
+    fn test_read_mealmaster() {
+        let recipes = read_mealmaster(COOKIES);
+        assert_eq!(recipes.len(), 2);
+
+        let cookies = &recipes[0];
+        assert_eq!(cookies.title, "Chocolate Chip Cookies");
+        assert_eq!(cookies.categories, vec!["Cookies", "Desserts"]);
+        assert_eq!(
+            cookies.yields,
+            Some(Measure::single(
+                Rational32::from_integer(36),
+                Unit::unitless("cookies".to_string())
+            ))
+        );
+        assert_eq!(cookies.ingredient_groups.len(), 2);
+        assert_eq!(
+            cookies.ingredient_groups[1].heading.as_deref(),
+            Some("FOR THE GLAZE")
+        );
+
+        let butter = &cookies.ingredient_groups[0].ingredients[0];
+        assert_eq!(butter.name, "Butter");
+        assert_eq!(butter.notes.as_deref(), Some("softened"));
+        assert_eq!(
+            butter.measure,
+            Some(Measure::single(Rational32::from_integer(1), Unit::Cup))
+        );
+        let eggs = &cookies.ingredient_groups[0].ingredients[2];
+        assert_eq!(eggs.modifiers, vec![Modifier::Large]);
+        assert_eq!(cookies.directions.len(), 2);
+        assert!(cookies.directions[0].ends_with("then beat in the eggs and vanilla."));
+
+        let salad = &recipes[1];
+        assert_eq!(
+            salad.yields,
+            Some(Measure::single(
+                Rational32::from_integer(4),
+                Unit::unitless("servings".to_string())
+            ))
+        );
+        // two column ingredients, with a line carrying on from the one before it
+        let names = salad.ingredient_groups[0]
+            .ingredients
+            .iter()
+            .map(ArchivedIngredient::text)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "Cucumbers, peeled and thinly sliced",
+                "Salt",
+                "White wine vinegar",
+                "Dill, chopped"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mealmaster_round_trip() {
+        let recipes = read_mealmaster(COOKIES);
+        let written = write_mealmaster(&recipes);
+
+        assert_eq!(read_mealmaster(&written), recipes);
+        // the first recipe is already laid out the way we write it
+        assert_eq!(
+            write_mealmaster(&recipes[..1]),
+            COOKIES.split("\n\n\n").next().unwrap().to_string() + "\n"
+        );
+    }
+}
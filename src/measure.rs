@@ -1,12 +1,11 @@
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
-use std::ptr::write;
 
 use num_rational::Rational32;
-use num_traits::{One, Zero};
+use num_traits::{CheckedDiv, CheckedMul, One, Zero};
 use serde::{Deserialize, Serialize};
 
-use crate::{Dimension, Magnitude, Unit};
+use crate::{Dimension, Unit, UnitSystem};
 
 #[derive(Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub enum Measure {
@@ -33,6 +32,10 @@ impl Measure {
         Measure::single(unit.from_base_value(base_value), unit)
     }
 
+    pub(crate) fn checked_from_base(base_value: Rational32, unit: Unit) -> Option<Measure> {
+        SingleMeasure::checked_from_base(base_value, unit).map(Measure::Single)
+    }
+
     pub fn is_integer(&self) -> bool {
         match self {
             Measure::Single(single) => single.value.is_integer(),
@@ -44,6 +47,13 @@ impl Measure {
         self.main_unit().dimension()
     }
 
+    pub fn main_value(&self) -> Rational32 {
+        match self {
+            Measure::Single(single) => single.value,
+            Measure::Multi(multi) => multi.first().unwrap().value,
+        }
+    }
+
     pub fn main_unit(&self) -> &Unit {
         match self {
             Measure::Single(single) => &single.unit,
@@ -57,9 +67,8 @@ impl Display for Measure {
         match self {
             Measure::Single(measure) => Display::fmt(measure, f),
             Measure::Multi(multi) => {
-                let first = true;
                 for (i, measure) in multi.iter().enumerate() {
-                    if !first && i < multi.len() - 1 {
+                    if i > 0 {
                         write!(f, " and ")?;
                     }
                     Display::fmt(measure, f)?;
@@ -89,12 +98,24 @@ impl SingleMeasure {
     }
 
     pub(crate) fn from_base(base_value: Rational32, unit: Unit) -> SingleMeasure {
-        SingleMeasure::new(base_value / unit.multiple(), unit)
+        SingleMeasure::new(unit.from_base_value(base_value), unit)
+    }
+
+    pub(crate) fn checked_from_base(base_value: Rational32, unit: Unit) -> Option<SingleMeasure> {
+        Some(SingleMeasure::new(
+            unit.checked_from_base_value(base_value)?,
+            unit,
+        ))
     }
 
     #[inline]
     pub(crate) fn base(&self) -> Rational32 {
-        self.value * self.unit.multiple()
+        self.unit.base_value(self.value)
+    }
+
+    /// Our value in the base unit, or [`None`] if it doesn't fit in our numbers
+    pub(crate) fn checked_base(&self) -> Option<Rational32> {
+        self.unit.checked_base_value(self.value)
     }
 
     pub(crate) fn base_trunc(&self) -> Rational32 {
@@ -108,9 +129,11 @@ impl SingleMeasure {
     pub fn is_good(&self) -> bool {
         self.value != Rational32::zero()
             && (self.value.is_integer() || {
-                COMMON_FRACTIONS
-                    .iter()
-                    .any(|fraction| (self.value / fraction).is_integer())
+                COMMON_FRACTIONS.iter().any(|fraction| {
+                    self.value
+                        .checked_div(fraction)
+                        .is_some_and(|whole| whole.is_integer())
+                })
             })
     }
 }
@@ -122,24 +145,61 @@ impl Display for SingleMeasure {
         } else {
             self.unit.description(self.value > Rational32::one())
         };
-        if self.value.is_integer() {
-            write!(f, "{} {unit_text}", self.value.numer(),)
-        } else if self.value > Rational32::one() {
-            let fract = self.value.fract();
+        if self.unit.system() == Some(UnitSystem::Metric) {
+            write!(f, "{} {unit_text}", Decimal(self.value))
+        } else {
+            write!(f, "{} {unit_text}", Fraction(self.value))
+        }
+    }
+}
+
+/// Formats a value the way recipes write them, as a mixed fraction (`1 1/2`)
+pub struct Fraction(pub Rational32);
+
+impl Display for Fraction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Fraction(value) = self;
+        if value.is_integer() {
+            write!(f, "{}", value.numer())
+        } else if *value > Rational32::one() {
+            let fract = value.fract();
             write!(
                 f,
-                "{} {}/{} {unit_text}",
-                self.value.to_integer(),
+                "{} {}/{}",
+                value.to_integer(),
                 fract.numer(),
-                fract.denom(),
+                fract.denom()
             )
         } else {
-            write!(
-                f,
-                "{}/{} {unit_text}",
-                self.value.numer(),
-                self.value.denom()
-            )
+            write!(f, "{}/{}", value.numer(), value.denom())
+        }
+    }
+}
+
+/// Formats a value as a decimal with up to 2 decimal places (`1.25`), the way metric measures are
+/// written
+pub struct Decimal(pub Rational32);
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Decimal(value) = self;
+        let Some(hundredths) = value.checked_mul(&Rational32::from_integer(100)) else {
+            // too big to write to two places, so write it as a fraction
+            return write!(f, "{value}");
+        };
+        let hundredths = hundredths.round().to_integer();
+        let (integer, fraction) = (hundredths / 100, (hundredths % 100).abs());
+        let sign = if hundredths < 0 && integer == 0 {
+            "-"
+        } else {
+            ""
+        };
+        if fraction == 0 {
+            write!(f, "{sign}{integer}")
+        } else if fraction % 10 == 0 {
+            write!(f, "{sign}{integer}.{}", fraction / 10)
+        } else {
+            write!(f, "{sign}{integer}.{fraction:02}")
         }
     }
 }
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use num_rational::Rational32;
+use serde::{Deserialize, Serialize};
+
+use crate::parser::MeasureToken;
+use crate::{Measure, Unit};
+
+/// A word between the number and the unit that describes the measure (`3 large eggs`,
+/// `1 heaping cup`)
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Debug, Copy, Clone)]
+pub enum Modifier {
+    // Size
+    Small,
+    Medium,
+    Large,
+    ExtraLarge,
+    Jumbo,
+    // Descriptor
+    Heaping,
+    Level,
+    Scant,
+    Generous,
+}
+
+pub static MODIFIERS: [Modifier; 9] = [
+    Modifier::Small,
+    Modifier::Medium,
+    Modifier::Large,
+    Modifier::ExtraLarge,
+    Modifier::Jumbo,
+    Modifier::Heaping,
+    Modifier::Level,
+    Modifier::Scant,
+    Modifier::Generous,
+];
+
+impl Modifier {
+    pub fn aliases(&self) -> &[&str] {
+        match self {
+            // Size
+            Modifier::Small => &["small", "sm"],
+            Modifier::Medium => &["medium", "med"],
+            Modifier::Large => &["large", "lg"],
+            Modifier::ExtraLarge => &["extra-large", "extra large", "xl"],
+            Modifier::Jumbo => &["jumbo"],
+            // Descriptor
+            Modifier::Heaping => &["heaping", "heaped", "rounded"],
+            Modifier::Level => &["level"],
+            Modifier::Scant => &["scant"],
+            Modifier::Generous => &["generous"],
+        }
+    }
+
+    /// Does this modifier describe how big the thing being counted is
+    pub fn is_size(&self) -> bool {
+        matches!(
+            self,
+            Modifier::Small
+                | Modifier::Medium
+                | Modifier::Large
+                | Modifier::ExtraLarge
+                | Modifier::Jumbo
+        )
+    }
+}
+
+impl Display for Modifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.aliases().first().unwrap())
+    }
+}
+
+/// How much a single item of each size weighs in grams, used to estimate the mass of counted
+/// ingredients like `3 large eggs`
+#[derive(Debug, Clone)]
+pub struct SizeWeights {
+    grams: HashMap<(String, Modifier), Rational32>,
+}
+
+impl SizeWeights {
+    pub fn empty() -> SizeWeights {
+        SizeWeights {
+            grams: HashMap::new(),
+        }
+    }
+
+    /// Record that one `ingredient` (singular, like `egg`) of `size` weighs `grams`
+    pub fn insert<R: Into<Rational32>>(&mut self, ingredient: &str, size: Modifier, grams: R) {
+        self.grams
+            .insert((ingredient.to_lowercase(), size), grams.into());
+    }
+
+    pub fn grams(&self, ingredient: &str, size: Modifier) -> Option<Rational32> {
+        let ingredient = ingredient.to_lowercase();
+        // try to turn plurals into the singular names we store
+        let names = [
+            Some(ingredient.as_str()),
+            ingredient.strip_suffix("es"),
+            ingredient.strip_suffix('s'),
+        ];
+
+        let grams = names
+            .into_iter()
+            .flatten()
+            .find_map(|name| self.grams.get(&(name.to_string(), size)).copied());
+
+        grams
+    }
+
+    /// Estimate the mass of a counted token like `3 large eggs`, assuming medium when no size is
+    /// given
+    pub fn estimate(&self, token: &MeasureToken) -> Option<Measure> {
+        let Measure::Single(single) = &token.measure else {
+            return None;
+        };
+        let Unit::Unitless { unit: ingredient } = &single.unit else {
+            return None;
+        };
+        let size = token
+            .modifiers
+            .iter()
+            .copied()
+            .find(Modifier::is_size)
+            .unwrap_or(Modifier::Medium);
+
+        self.grams(ingredient, size)
+            .map(|grams| Measure::single(single.value * grams, Unit::Gram))
+    }
+}
+
+impl Default for SizeWeights {
+    /// Rough weights of common ingredients, without their shells or skins
+    fn default() -> Self {
+        let mut weights = SizeWeights::empty();
+        let table: [(&str, [(Modifier, i32); 3]); 6] = [
+            (
+                "egg",
+                [
+                    (Modifier::Small, 38),
+                    (Modifier::Medium, 44),
+                    (Modifier::Large, 50),
+                ],
+            ),
+            (
+                "onion",
+                [
+                    (Modifier::Small, 70),
+                    (Modifier::Medium, 110),
+                    (Modifier::Large, 150),
+                ],
+            ),
+            (
+                "potato",
+                [
+                    (Modifier::Small, 170),
+                    (Modifier::Medium, 210),
+                    (Modifier::Large, 300),
+                ],
+            ),
+            (
+                "carrot",
+                [
+                    (Modifier::Small, 50),
+                    (Modifier::Medium, 60),
+                    (Modifier::Large, 70),
+                ],
+            ),
+            (
+                "lemon",
+                [
+                    (Modifier::Small, 60),
+                    (Modifier::Medium, 85),
+                    (Modifier::Large, 110),
+                ],
+            ),
+            (
+                "apple",
+                [
+                    (Modifier::Small, 150),
+                    (Modifier::Medium, 180),
+                    (Modifier::Large, 220),
+                ],
+            ),
+        ];
+        for (ingredient, sizes) in table {
+            for (size, grams) in sizes {
+                weights.insert(ingredient, size, grams);
+            }
+        }
+        weights.insert("egg", Modifier::ExtraLarge, 56);
+        weights.insert("egg", Modifier::Jumbo, 63);
+
+        weights
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::parse_recipe;
+
+    use super::*;
+
+    #[test]
+    fn test_estimate() {
+        let weights = SizeWeights::default();
+        let estimate = |recipe| {
+            parse_recipe(recipe)
+                .unwrap()
+                .tokens
+                .first()
+                .and_then(|token| weights.estimate(token))
+        };
+
+        assert_eq!(
+            estimate("3 large eggs"),
+            Some(Measure::single(Rational32::from_integer(150), Unit::Gram))
+        );
+        assert_eq!(
+            estimate("2 Medium Onions"),
+            Some(Measure::single(Rational32::from_integer(220), Unit::Gram))
+        );
+        assert_eq!(
+            estimate("1 potatoes"),
+            Some(Measure::single(Rational32::from_integer(210), Unit::Gram))
+        );
+        assert_eq!(
+            estimate("2 extra-large eggs"),
+            Some(Measure::single(Rational32::from_integer(112), Unit::Gram))
+        );
+        assert_eq!(estimate("2 large pumpkins"), None);
+        assert_eq!(estimate("2 cups flour"), None);
+    }
+}
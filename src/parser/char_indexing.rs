@@ -9,7 +9,7 @@ pub trait CharIndexing {
     fn char_index_for_byte(&self, byte_offset: usize) -> usize;
 }
 
-impl<'a> CharIndexing for &'a str {
+impl CharIndexing for &str {
     fn char_slice(&self, char_range: Range<usize>) -> Option<Self> {
         if !char_range.is_empty() {
             let mut iter = self.char_indices().map(|(i, _)| i);
@@ -32,7 +32,7 @@ impl<'a> CharIndexing for &'a str {
         let char_offset = self.chars().enumerate().find_map(|(i, c)| {
             if byte_offset > 0 {
                 // if we index into the middle of a char we'll still get it
-                byte_offset = byte_offset.checked_sub(c.len_utf8()).unwrap_or_default();
+                byte_offset = byte_offset.saturating_sub(c.len_utf8());
                 None
             } else {
                 Some(i)
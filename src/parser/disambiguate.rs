@@ -0,0 +1,318 @@
+use std::ops::Range;
+
+use num_rational::Rational32;
+
+use crate::parser::{Confidence, MeasureToken, ParserInput};
+use crate::{Dimension, Measure, Unit};
+
+/// Words that hint we're talking about a temperature when they show up in the same sentence
+const TEMPERATURE_WORDS: [&str; 12] = [
+    "oven",
+    "bake",
+    "baking",
+    "preheat",
+    "preheated",
+    "heat",
+    "roast",
+    "broil",
+    "fry",
+    "frying",
+    "temperature",
+    "thermometer",
+];
+
+/// Words that can follow a unit without being the ingredient it's measuring
+const CONNECTING_WORDS: [&str; 10] = [
+    "for", "until", "and", "or", "to", "then", "in", "on", "at", "with",
+];
+
+/// No recipe asks for this many cups, but plenty of ovens are set to it
+const MAX_REASONABLE_VOLUME: i32 = 50;
+
+/// Ovens set in Celsius are almost always below this, ovens set in Fahrenheit are above it
+const MIN_FAHRENHEIT_OVEN: i32 = 200;
+/// Ovens set in Fahrenheit can be below this but ovens set in Celsius almost never are
+const MAX_CELSIUS_OVEN: i32 = 260;
+
+/// Pick between the units of tokens whose unit text is ambiguous (`C`, `T` / `t`, `degrees`)
+/// based on the text surrounding them
+pub(crate) fn disambiguate(raw: ParserInput, tokens: &mut [MeasureToken]) {
+    let document_temperature = document_temperature(tokens);
+
+    for token in tokens.iter_mut() {
+        let Range { start, end } = token.byte_range();
+        let (start, end) = (start - raw.position.byte, end - raw.position.byte);
+        if token.alternatives.is_empty() {
+            continue;
+        }
+
+        let context = Context::new(&raw.input[..start], &raw.input[end..], token);
+        let mut candidates = vec![token.measure.main_unit().clone()];
+        candidates.append(&mut token.alternatives);
+
+        let (unit, confidence) = choose(&candidates, &context, document_temperature.as_ref());
+        candidates.retain(|candidate| *candidate != unit);
+
+        if let Measure::Single(single) = &mut token.measure {
+            single.unit = unit;
+        }
+        token.confidence = confidence;
+        token.alternatives = candidates;
+    }
+}
+
+/// If a recipe spells out a temperature unit somewhere (`°F`, `Celsius`) it's probably using it
+/// everywhere
+fn document_temperature(tokens: &[MeasureToken]) -> Option<Unit> {
+    let mut explicit = tokens.iter().filter_map(|token| {
+        let unit = token.measure.main_unit();
+        (unit.dimension() == Dimension::Temperature && token.alternatives.is_empty())
+            .then_some(unit)
+    });
+
+    let first = explicit.next()?;
+    explicit.all(|unit| unit == first).then(|| first.clone())
+}
+
+struct Context<'a> {
+    value: Rational32,
+    /// The sentence leading up to the token
+    before: &'a str,
+    /// The word right after the token
+    next_word: Option<&'a str>,
+    /// Whether the line the token is on is written in all caps, which means the case of the unit
+    /// tells us nothing
+    shouting: bool,
+}
+
+impl<'a> Context<'a> {
+    fn new(before: &'a str, after: &'a str, token: &MeasureToken) -> Context<'a> {
+        let value = match &token.measure {
+            Measure::Single(single) => single.value,
+            Measure::Multi(multi) => multi.first().map(|m| m.value).unwrap_or_default(),
+        };
+
+        let line_before = before.rsplit('\n').next().unwrap_or_default();
+        let line_after = after.split('\n').next().unwrap_or_default();
+        let sentence_before = line_before.rsplit(". ").next().unwrap_or_default();
+        let next_word = line_after
+            .split(|c: char| !c.is_alphabetic())
+            .find(|word| !word.is_empty());
+
+        let line = [line_before, line_after, &token.raw];
+        let shouting = line
+            .iter()
+            .all(|part| !part.chars().any(char::is_lowercase));
+
+        Context {
+            value,
+            before: sentence_before,
+            next_word,
+            shouting,
+        }
+    }
+
+    fn mentions_temperature(&self) -> bool {
+        let before = self
+            .before
+            .split(|c: char| !c.is_alphabetic())
+            .any(is_temperature_word);
+
+        before || self.next_word.is_some_and(is_temperature_word)
+    }
+
+    /// Is the token followed by something that looks like it's being measured, like `flour`
+    fn followed_by_ingredient(&self) -> bool {
+        self.next_word.is_some_and(|word| {
+            !is_temperature_word(word)
+                && !CONNECTING_WORDS
+                    .iter()
+                    .any(|connecting| connecting.eq_ignore_ascii_case(word))
+        })
+    }
+}
+
+fn is_temperature_word(word: &str) -> bool {
+    TEMPERATURE_WORDS
+        .iter()
+        .any(|temperature| temperature.eq_ignore_ascii_case(word))
+}
+
+fn choose(
+    candidates: &[Unit],
+    context: &Context,
+    document_temperature: Option<&Unit>,
+) -> (Unit, Confidence) {
+    let has = |dimension| {
+        candidates
+            .iter()
+            .any(|candidate| candidate.dimension() == dimension)
+    };
+
+    if has(Dimension::Volume) && has(Dimension::Temperature) {
+        let mut temperature_evidence = 0;
+        let mut volume_evidence = 0;
+        if context.value > Rational32::from_integer(MAX_REASONABLE_VOLUME) {
+            temperature_evidence += 2;
+        }
+        if !context.value.is_integer() {
+            volume_evidence += 1;
+        }
+        if context.mentions_temperature() {
+            temperature_evidence += 1;
+        } else if context.followed_by_ingredient() {
+            volume_evidence += 1;
+        }
+
+        let dimension = if temperature_evidence > volume_evidence {
+            Dimension::Temperature
+        } else if volume_evidence > temperature_evidence {
+            Dimension::Volume
+        } else {
+            return (candidates[0].clone(), Confidence::Guess);
+        };
+
+        let remaining = candidates
+            .iter()
+            .filter(|candidate| candidate.dimension() == dimension)
+            .cloned()
+            .collect::<Vec<_>>();
+        let (unit, confidence) = choose(&remaining, context, document_temperature);
+        let confidence = if temperature_evidence == 0 || volume_evidence == 0 {
+            confidence
+        } else {
+            confidence.min(Confidence::Guess)
+        };
+
+        (unit, confidence)
+    } else if has(Dimension::Temperature) && candidates.len() > 1 {
+        choose_temperature(candidates, context, document_temperature)
+    } else if candidates.len() > 1 {
+        // the units only differ by case (t vs T), so trust the case unless it was lost
+        let confidence = if context.shouting {
+            Confidence::Guess
+        } else {
+            Confidence::Likely
+        };
+        (candidates[0].clone(), confidence)
+    } else {
+        (candidates[0].clone(), Confidence::Likely)
+    }
+}
+
+fn choose_temperature(
+    candidates: &[Unit],
+    context: &Context,
+    document_temperature: Option<&Unit>,
+) -> (Unit, Confidence) {
+    if let Some(unit) = document_temperature.filter(|unit| candidates.contains(unit)) {
+        return (unit.clone(), Confidence::Likely);
+    }
+
+    let value = context.value;
+    let preferred = if value > Rational32::from_integer(MAX_CELSIUS_OVEN) {
+        Some(Unit::Fahrenheit)
+    } else if value < Rational32::from_integer(MIN_FAHRENHEIT_OVEN)
+        && context.mentions_temperature()
+    {
+        Some(Unit::Celsius)
+    } else {
+        None
+    };
+
+    match preferred.filter(|unit| candidates.contains(unit)) {
+        Some(unit) => (unit, Confidence::Likely),
+        None => (candidates[0].clone(), Confidence::Guess),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::parse_recipe;
+
+    use super::*;
+
+    fn units(recipe: &str) -> Vec<(Unit, Confidence)> {
+        parse_recipe(recipe)
+            .unwrap()
+            .tokens
+            .into_iter()
+            .map(|token| (token.measure.main_unit().clone(), token.confidence))
+            .collect()
+    }
+
+    #[test]
+    fn test_cup_or_celsius() {
+        assert_eq!(units("2 C flour"), vec![(Unit::Cup, Confidence::Likely)]);
+        assert_eq!(units("1/2 C"), vec![(Unit::Cup, Confidence::Likely)]);
+        assert_eq!(
+            units("60 C sugar"),
+            vec![(Unit::Celsius, Confidence::Guess)]
+        );
+        assert_eq!(
+            units("Preheat the oven to 180 C."),
+            vec![(Unit::Celsius, Confidence::Likely)]
+        );
+        assert_eq!(
+            units("Bake at 180C for 20 minutes"),
+            vec![
+                (Unit::Celsius, Confidence::Likely),
+                (Unit::Minute, Confidence::Certain)
+            ]
+        );
+        assert_eq!(units("2 C"), vec![(Unit::Cup, Confidence::Guess)]);
+
+        let recipe = parse_recipe("Preheat oven to 200 C").unwrap();
+        assert_eq!(recipe.tokens[0].alternatives, vec![Unit::Cup]);
+    }
+
+    #[test]
+    fn test_teaspoon_or_tablespoon() {
+        assert_eq!(
+            units("1 T sugar"),
+            vec![(Unit::Tablespoon, Confidence::Likely)]
+        );
+        assert_eq!(
+            units("1 t salt"),
+            vec![(Unit::Teaspoon, Confidence::Likely)]
+        );
+        assert_eq!(
+            units("1 T SUGAR"),
+            vec![(Unit::Tablespoon, Confidence::Guess)]
+        );
+    }
+
+    #[test]
+    fn test_degrees() {
+        assert_eq!(
+            units("Bake at 350 degrees"),
+            vec![(Unit::Fahrenheit, Confidence::Likely)]
+        );
+        assert_eq!(
+            units("Bake at 180 degrees"),
+            vec![(Unit::Celsius, Confidence::Likely)]
+        );
+        assert_eq!(
+            units("Bake at 225 degrees"),
+            vec![(Unit::Fahrenheit, Confidence::Guess)]
+        );
+        assert_eq!(
+            units("Bake at 225 degrees C"),
+            vec![(Unit::Celsius, Confidence::Certain)]
+        );
+        assert_eq!(
+            units("Heat the oil to 375°F. Roast at 225 degrees"),
+            vec![
+                (Unit::Fahrenheit, Confidence::Certain),
+                (Unit::Fahrenheit, Confidence::Likely)
+            ]
+        );
+        assert_eq!(
+            units("Heat to 190°C, then bake at 200 degrees"),
+            vec![
+                (Unit::Celsius, Confidence::Certain),
+                (Unit::Celsius, Confidence::Likely)
+            ]
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt::{Debug, Formatter};
+use std::ops::Range;
+use std::slice;
+
+use crate::parser::{MeasureToken, Recipe};
+
+/// A piece of a recipe, either a measure or the text between measures
+#[derive(Eq, PartialEq, Clone)]
+pub enum DocumentToken<'r, 'a> {
+    Measure(&'r MeasureToken<'a>),
+    Other {
+        raw: &'a str,
+        /// The chars this text covers in the recipe
+        range: Range<usize>,
+    },
+}
+
+impl<'r, 'a> DocumentToken<'r, 'a> {
+    /// The chars this token covers in the recipe
+    pub fn range(&self) -> Range<usize> {
+        match self {
+            DocumentToken::Measure(token) => token.full_range(),
+            DocumentToken::Other { range, .. } => range.clone(),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        match self {
+            DocumentToken::Measure(token) => &token.raw,
+            DocumentToken::Other { raw, .. } => raw,
+        }
+    }
+}
+
+/// Iterates over every [`DocumentToken`] in a [`Recipe`] in order, so the text of all the tokens
+/// joined together is the original recipe
+pub struct DocumentTokens<'r, 'a> {
+    raw: &'a str,
+    tokens: slice::Iter<'r, MeasureToken<'a>>,
+    /// A measure we found but still need to return after the text before it
+    pending: Option<&'r MeasureToken<'a>>,
+    /// How far into `raw` we've returned tokens for
+    char_index: usize,
+    byte_index: usize,
+}
+
+impl<'a> Recipe<'a> {
+    pub fn iter(&self) -> DocumentTokens<'_, 'a> {
+        DocumentTokens {
+            raw: self.raw,
+            tokens: self.tokens.iter(),
+            pending: None,
+            char_index: 0,
+            byte_index: 0,
+        }
+    }
+}
+
+impl<'r, 'a> IntoIterator for &'r Recipe<'a> {
+    type Item = DocumentToken<'r, 'a>;
+    type IntoIter = DocumentTokens<'r, 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'r, 'a> DocumentTokens<'r, 'a> {
+    fn measure(&mut self, token: &'r MeasureToken<'a>) -> DocumentToken<'r, 'a> {
+        self.char_index = token.full_range().end;
+        self.byte_index += token.raw.len();
+
+        DocumentToken::Measure(token)
+    }
+
+    /// Return the text from where we are up to `char_end`
+    fn other(&mut self, char_end: usize) -> DocumentToken<'r, 'a> {
+        let remaining = &self.raw[self.byte_index..];
+        let byte_length = remaining
+            .char_indices()
+            .nth(char_end - self.char_index)
+            .map(|(i, _)| i)
+            .unwrap_or(remaining.len());
+        let token = DocumentToken::Other {
+            raw: &remaining[..byte_length],
+            range: self.char_index..char_end,
+        };
+        self.char_index = char_end;
+        self.byte_index += byte_length;
+
+        token
+    }
+}
+
+impl<'r, 'a> Iterator for DocumentTokens<'r, 'a> {
+    type Item = DocumentToken<'r, 'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(token) = self.pending.take() {
+            return Some(self.measure(token));
+        }
+
+        match self.tokens.next() {
+            Some(token) if token.full_range().start > self.char_index => {
+                self.pending = Some(token);
+                Some(self.other(token.full_range().start))
+            }
+            Some(token) => Some(self.measure(token)),
+            None if self.byte_index < self.raw.len() => {
+                let char_end = self.char_index + self.raw[self.byte_index..].chars().count();
+                Some(self.other(char_end))
+            }
+            None => None,
+        }
+    }
+}
+
+impl<'r, 'a> Debug for DocumentToken<'r, 'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DocumentToken::Measure(token) => write!(f, "({token:?})"),
+            DocumentToken::Other { raw, range } => write!(f, "({range:?}: \"{raw}\")"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::parse_recipe;
+    use crate::Unit;
+
+    use super::*;
+
+    #[test]
+    fn test_document_tokens() {
+        let raw = "Mix ½ cup sugar with 2 tsp salt, then 3 eggs";
+        let recipe = parse_recipe(raw).unwrap();
+        let tokens = recipe.iter().collect::<Vec<_>>();
+
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|token| (token.text(), token.range()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Mix ", 0..4),
+                ("½ cup", 4..9),
+                (" sugar with ", 9..21),
+                ("2 tsp", 21..26),
+                (" salt, then ", 26..38),
+                ("3 eggs", 38..44),
+            ]
+        );
+        assert!(matches!(
+            tokens[1],
+            DocumentToken::Measure(MeasureToken { measure, .. }) if *measure.main_unit() == Unit::Cup
+        ));
+        assert_eq!(
+            tokens.iter().map(DocumentToken::text).collect::<String>(),
+            raw
+        );
+    }
+
+    #[test]
+    fn test_edges() {
+        let tokens = |raw| {
+            parse_recipe(raw)
+                .unwrap()
+                .iter()
+                .map(|token| (token.text().to_string(), token.range()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(tokens(""), vec![]);
+        assert_eq!(tokens("salt"), vec![("salt".to_string(), 0..4)]);
+        assert_eq!(tokens("1 cup"), vec![("1 cup".to_string(), 0..5)]);
+        assert_eq!(
+            tokens("1 cup2 tsp"),
+            vec![("1 cup".to_string(), 0..5), ("2 tsp".to_string(), 5..10)]
+        );
+        assert_eq!(
+            tokens("½ cup ⅓"),
+            vec![("½ cup".to_string(), 0..5), (" ⅓".to_string(), 5..7)]
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Unit, UNITFUL_UNITS};
+
+/// Aliases shorter than this are too easy to hit by accident (`t`, `c`, `Tb`)
+const MIN_FUZZY_LENGTH: usize = 3;
+
+/// Records that the unit text was misspelled and which alias we matched it to
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone)]
+pub struct FuzzyMatch {
+    /// The alias closest to the unit text, suitable for replacing it
+    pub correction: String,
+    /// The number of edits between the unit text and `correction`
+    pub distance: usize,
+}
+
+/// Find the unit with an alias closest to `raw_unit`, as long as it's within `threshold` edits.
+/// We only consider aliases that start with the same letter since that's rarely the typo.
+pub(crate) fn fuzzy_unit(raw_unit: &str, threshold: usize) -> Option<(Unit, FuzzyMatch)> {
+    let raw_unit = raw_unit.to_lowercase();
+    let first = raw_unit.chars().next()?;
+    if raw_unit.chars().count() < MIN_FUZZY_LENGTH {
+        return None;
+    }
+
+    let mut best: Option<(Unit, FuzzyMatch)> = None;
+    for unit in UNITFUL_UNITS.iter() {
+        for alias in unit.aliases() {
+            let lowercase_alias = alias.to_lowercase();
+            if lowercase_alias.chars().count() < MIN_FUZZY_LENGTH
+                || !lowercase_alias.starts_with(first)
+            {
+                continue;
+            }
+
+            let distance = edit_distance(&raw_unit, &lowercase_alias);
+            let closer = best
+                .as_ref()
+                .is_none_or(|(_, fuzzy)| distance < fuzzy.distance);
+            if distance <= threshold && closer {
+                best = Some((
+                    unit.clone(),
+                    FuzzyMatch {
+                        correction: alias.to_string(),
+                        distance,
+                    },
+                ));
+            }
+        }
+    }
+
+    best
+}
+
+/// The optimal string alignment distance between two strings, which counts insertions, deletions,
+/// substitutions and swapping two adjacent chars as a single edit
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    // we need the previous 2 rows for transpositions
+    let mut before_previous = vec![0; b.len() + 1];
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before_previous[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before_previous, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("cup", ""), 3);
+        assert_eq!(edit_distance("cups", "cups"), 0);
+        assert_eq!(edit_distance("cupss", "cups"), 1);
+        assert_eq!(edit_distance("teaspon", "teaspoon"), 1);
+        assert_eq!(edit_distance("tsbp", "tbsp"), 1);
+        assert_eq!(edit_distance("tbls", "tbsp"), 2);
+        assert_eq!(edit_distance("½cup", "cup"), 1);
+    }
+
+    #[test]
+    fn test_fuzzy_unit() {
+        let corrected = |raw, threshold| {
+            fuzzy_unit(raw, threshold).map(|(unit, fuzzy)| (unit, fuzzy.correction))
+        };
+
+        assert_eq!(
+            corrected("teaspon", 1),
+            Some((Unit::Teaspoon, "teaspoon".to_string()))
+        );
+        assert_eq!(
+            corrected("tablspoons", 1),
+            Some((Unit::Tablespoon, "tablespoons".to_string()))
+        );
+        assert_eq!(corrected("cupss", 1), Some((Unit::Cup, "cups".to_string())));
+        assert_eq!(corrected("Cupss", 1), Some((Unit::Cup, "cups".to_string())));
+        assert_eq!(corrected("tbls", 1), None);
+        assert_eq!(
+            corrected("tbls", 2),
+            Some((Unit::Tablespoon, "tbsp".to_string()))
+        );
+        assert_eq!(corrected("large", 2), None);
+        assert_eq!(corrected("eggs", 2), None);
+        assert_eq!(corrected("ts", 2), None);
+    }
+}
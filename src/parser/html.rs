@@ -0,0 +1,479 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use num_rational::Rational32;
+
+use crate::parser::parse_yield::find_yield;
+use crate::parser::{
+    tokenize, MeasureToken, ParseOptions, ParserInput, Position, Recipe, Span, Yield,
+};
+use crate::{Decimal, Dimension, Magnitude, Measure, ParseError, Unit};
+
+/// Elements whose text is never part of the recipe
+const SKIPPED_ELEMENTS: [&str; 4] = ["script", "style", "template", "noscript"];
+/// Elements that sit inside a run of text, so a measure can start in one and end in another
+/// (`<b>2</b> cups`). Every other element breaks the text up.
+const INLINE_ELEMENTS: [&str; 23] = [
+    "a", "abbr", "b", "bdi", "bdo", "cite", "code", "data", "dfn", "em", "font", "i", "kbd",
+    "mark", "q", "s", "small", "span", "strong", "sub", "sup", "time", "u",
+];
+/// Elements that don't have a closing tag
+const VOID_ELEMENTS: [&str; 13] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// A recipe read from the text of an HTML page. [`Recipe::raw`] is the HTML itself, so every
+/// range points into the original markup.
+pub struct HtmlRecipe<'a> {
+    pub recipe: Recipe<'a>,
+}
+
+pub fn parse_html(input: &str) -> Result<HtmlRecipe<'_>, ParseError> {
+    parse_html_with(input, &ParseOptions::default())
+}
+
+pub fn parse_html_with<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> Result<HtmlRecipe<'a>, ParseError> {
+    let (mut tokens, mut warnings, mut yields) = (vec![], vec![], None);
+    for segment in segments(input) {
+        let text = ParserInput::from(segment.text.as_str());
+        let (found, skipped) = tokenize(text, options)?;
+        tokens.extend(found.into_iter().map(|token| segment.token(input, token)));
+        warnings.extend(skipped.into_iter().map(|mut warning| {
+            let span = segment.span(warning.span().chars.clone());
+            *warning.span_mut() = span;
+            warning
+        }));
+        yields = yields.or_else(|| {
+            find_yield(text).map(|found| Yield {
+                range: segment.span(found.range).chars,
+                number_range: segment.span(found.number_range).chars,
+                ..found
+            })
+        });
+    }
+
+    Ok(HtmlRecipe {
+        recipe: Recipe::new(input, tokens, warnings, yields),
+    })
+}
+
+impl<'a> HtmlRecipe<'a> {
+    /// The HTML with each measure wrapped in a `<data>` element giving its value in a standard
+    /// unit for its dimension (milliliters, grams, Celsius, seconds, centimeters), for example
+    /// `<data class="measure" value="240" data-unit="milliliter" data-dimension="volume">1 cup</data>`.
+    /// Measures that can't be wrapped without breaking up other elements, or that are too big to
+    /// write in the standard unit, are left as they are.
+    pub fn annotated(&self) -> String {
+        let html = self.recipe.raw;
+        let mut output = String::with_capacity(html.len());
+        let mut written = 0;
+        for token in &self.recipe.tokens {
+            let Some(Range { start, end }) = wrappable(html, token.byte_range()) else {
+                continue;
+            };
+            if start < written {
+                continue;
+            }
+            let Some((value, unit)) = canonical(&token.measure) else {
+                continue;
+            };
+            output.push_str(&html[written..start]);
+            output.push_str(&format!(
+                r#"<data class="measure" value="{}" data-unit="{}" data-dimension="{}">"#,
+                Decimal(value),
+                escape_attribute(&unit),
+                format!("{:?}", token.measure.dimension()).to_lowercase(),
+            ));
+            output.push_str(&html[start..end]);
+            output.push_str("</data>");
+            written = end;
+        }
+        output.push_str(&html[written..]);
+
+        output
+    }
+}
+
+/// A measure's value in the unit we use for its dimension in annotations, [`None`] if it doesn't
+/// fit in our numbers
+fn canonical(measure: &Measure) -> Option<(Rational32, String)> {
+    let unit = match measure.dimension() {
+        Dimension::Volume => Unit::Milliliter,
+        Dimension::Mass => Unit::Gram,
+        Dimension::Temperature => Unit::Celsius,
+        Dimension::Time => Unit::Second,
+        Dimension::Length => Unit::Centimeter,
+        Dimension::Unitless => {
+            let noun = match measure.main_unit() {
+                Unit::Unitless { unit } => unit.clone(),
+                unit => unit.description(false).to_string(),
+            };
+            return Some((measure.main_value(), noun));
+        }
+    };
+    let name = unit.description(false).to_string();
+
+    let value = Magnitude::checked_from(measure)?
+        .checked_measure(unit)?
+        .main_value();
+
+    Some((value, name))
+}
+
+fn escape_attribute(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+}
+
+/// Text pulled out of the HTML, with where each of its chars came from
+#[derive(Default)]
+struct Segment {
+    text: String,
+    /// Where each char of `text` starts in the HTML
+    starts: Vec<Position>,
+    /// Where each char of `text` ends in the HTML
+    ends: Vec<Position>,
+}
+
+impl Segment {
+    fn push(&mut self, c: char, start: Position, end: Position) {
+        self.text.push(c);
+        self.starts.push(start);
+        self.ends.push(end);
+    }
+
+    /// Where a range of chars in our text came from in the HTML
+    fn positions(&self, range: Range<usize>) -> Range<Position> {
+        let end = |index: usize| match index.checked_sub(1) {
+            Some(last) => self.ends[last],
+            None => self.starts[0],
+        };
+        if range.is_empty() {
+            end(range.start)..end(range.end)
+        } else {
+            self.starts[range.start]..end(range.end)
+        }
+    }
+
+    fn span(&self, range: Range<usize>) -> Span {
+        let Range { start, end } = self.positions(range);
+
+        Span::new(start.char..end.char, start.byte..end.byte)
+    }
+
+    /// Move a token found in our text over to the HTML
+    fn token<'a>(&self, html: &'a str, token: MeasureToken) -> MeasureToken<'a> {
+        let number = self.positions(token.number_range.clone());
+        let unit = self.positions(token.unit_range.clone());
+
+        MeasureToken {
+            number_range: number.start.char..number.end.char,
+            unit_range: unit.start.char..unit.end.char,
+            raw: Cow::Borrowed(&html[number.start.byte..unit.end.byte]),
+            start: number.start,
+            measure: token.measure,
+            confidence: token.confidence,
+            alternatives: token.alternatives,
+            fuzzy: token.fuzzy,
+            modifiers: token.modifiers,
+        }
+    }
+}
+
+/// Split the text of the HTML into runs that measures can be found in, skipping tags, comments
+/// and scripts and decoding character references
+fn segments(html: &str) -> Vec<Segment> {
+    let mut segments = vec![];
+    let mut segment = Segment::default();
+    let mut position = Position::default();
+    while position.byte < html.len() {
+        let rest = &html[position.byte..];
+        if let Some(tag) = Tag::parse(html, position.byte) {
+            let mut end = tag.end;
+            if !tag.closing && !tag.self_closing && is_one_of(tag.name, &SKIPPED_ELEMENTS) {
+                end = find_closing(html, end, tag.name).unwrap_or(html.len());
+            }
+            if !tag.name.is_empty() && !is_one_of(tag.name, &INLINE_ELEMENTS) {
+                segments.push(std::mem::take(&mut segment));
+            }
+            position = position.advance(&rest[..end - position.byte]);
+        } else {
+            let (decoded, length) = entity(rest)
+                .unwrap_or_else(|| rest.chars().next().map(|c| (c, c.len_utf8())).unwrap());
+            let end = position.advance(&rest[..length]);
+            segment.push(decoded, position, end);
+            position = end;
+        }
+    }
+    segments.push(segment);
+    segments.retain(|segment| !segment.text.trim().is_empty());
+
+    segments
+}
+
+fn is_one_of(name: &str, names: &[&str]) -> bool {
+    names.iter().any(|other| other.eq_ignore_ascii_case(name))
+}
+
+/// A tag, comment or doctype in the HTML
+struct Tag<'a> {
+    /// Empty for comments and doctypes
+    name: &'a str,
+    closing: bool,
+    self_closing: bool,
+    /// The byte just past the `>`
+    end: usize,
+}
+
+impl<'a> Tag<'a> {
+    fn parse(html: &'a str, start: usize) -> Option<Tag<'a>> {
+        let rest = &html[start..];
+        if !rest.starts_with('<') {
+            return None;
+        }
+        let after = |found: Option<usize>, length: usize| {
+            found.map_or(html.len(), |found| start + found + length)
+        };
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let end = after(comment.find("-->").map(|found| found + 4), 3);
+            return Some(Tag::other(end));
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            return Some(Tag::other(after(rest.find('>'), 1)));
+        }
+
+        let (closing, name_start) = match rest.strip_prefix("</") {
+            Some(_) => (true, 2),
+            None => (false, 1),
+        };
+        let name_length = rest[name_start..]
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+            .unwrap_or(rest.len() - name_start);
+        let name = &rest[name_start..name_start + name_length];
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return None;
+        }
+
+        // find the end of the tag, skipping over `>` in quoted attribute values
+        let mut quote = None;
+        for (i, c) in rest.char_indices().skip(name_start + name_length) {
+            match (quote, c) {
+                (Some(open), c) if c == open => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '>') => {
+                    return Some(Tag {
+                        name,
+                        closing,
+                        self_closing: rest[..i].ends_with('/') || is_one_of(name, &VOID_ELEMENTS),
+                        end: start + i + 1,
+                    })
+                }
+                (None, _) => {}
+            }
+        }
+
+        None
+    }
+
+    fn other(end: usize) -> Tag<'a> {
+        Tag {
+            name: "",
+            closing: false,
+            self_closing: true,
+            end,
+        }
+    }
+}
+
+/// The byte after the tag that closes a `name` element opened before `from`
+fn find_closing(html: &str, from: usize, name: &str) -> Option<usize> {
+    let lowercase = html[from..].to_ascii_lowercase();
+    let found = lowercase.find(&format!("</{}", name.to_ascii_lowercase()))?;
+
+    Tag::parse(html, from + found).map(|tag| tag.end)
+}
+
+/// Decode the character reference at the start of `text`, along with how long it is
+fn entity(text: &str) -> Option<(char, usize)> {
+    let text = text.strip_prefix('&')?;
+    let end = text.find(';').filter(|&end| end <= 10)?;
+    let name = &text[..end];
+    let decoded = match name {
+        "nbsp" | "ensp" | "emsp" | "thinsp" => ' ',
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "frac12" => '½',
+        "frac13" => '⅓',
+        "frac23" => '⅔',
+        "frac14" => '¼',
+        "frac34" => '¾',
+        "frac18" => '⅛',
+        "frasl" => '⁄',
+        "deg" => '°',
+        "ndash" => '–',
+        "mdash" => '—',
+        _ => {
+            let number = name.strip_prefix('#')?;
+            let code = match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => number.parse().ok()?,
+            };
+            char::from_u32(code)?
+        }
+    };
+
+    Some((decoded, end + 2))
+}
+
+/// Grow a range of the HTML until the tags inside it are balanced, so we can wrap it in another
+/// element (`2</span> <span>cups` becomes `<span>2</span> <span>cups</span>`). Each side grows on
+/// its own, so `2</b> cups` becomes `<b>2</b> cups`. [`None`] if we can't.
+fn wrappable(html: &str, mut range: Range<usize>) -> Option<Range<usize>> {
+    loop {
+        let (unopened, unclosed) = unbalanced(&html[..range.end], range.start)?;
+        if !unopened && !unclosed {
+            return Some(range);
+        }
+        if unopened {
+            let (start, _) = html[..range.start]
+                .rfind('<')
+                .and_then(|start| Some((start, Tag::parse(html, start)?)))
+                .filter(|(_, tag)| tag.end == range.start && !tag.closing && !tag.self_closing)?;
+            range.start = start;
+        }
+        if unclosed {
+            range.end = Tag::parse(html, range.end).filter(|tag| tag.closing)?.end;
+        }
+    }
+}
+
+/// Whether the tags in `html[start..]` close tags opened before it, and whether they leave tags
+/// open. [`None`] if they cross each other (`<b><i></b>`), which growing can't fix.
+fn unbalanced(html: &str, start: usize) -> Option<(bool, bool)> {
+    let mut open = vec![];
+    let mut unopened = false;
+    let mut i = start;
+    while let Some(found) = html[i..].find('<') {
+        let Some(tag) = Tag::parse(html, i + found) else {
+            i += found + 1;
+            continue;
+        };
+        if tag.closing {
+            match open.pop() {
+                Some(name) if tag.name.eq_ignore_ascii_case(name) => {}
+                Some(_) => return None,
+                None => unopened = true,
+            }
+        } else if !tag.self_closing {
+            open.push(tag.name);
+        }
+        i = tag.end;
+    }
+
+    Some((unopened, !open.is_empty()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PAGE: &str = r#"<html><head><title>Pancakes</title>
+<script>var cups = "4 cups";</script><style>.x { width: 5em }</style></head>
+<body data-size="6 cups">
+<h1>Pancakes</h1>
+<p>Serves 4</p>
+<ul>
+<li>1&frac12; cups flour</li>
+<li><span class="qty">2</span> <span class="unit">tbsp</span> sugar</li>
+<li>3&nbsp;large eggs</li>
+</ul>
+<p>Bake at <b>350&deg;F</b> for 20 minutes.</p>
+<p>Sprinkle with <b>2</b> tsp sugar.</p>
+<!-- 7 cups -->
+</body></html>"#;
+
+    #[test]
+    fn test_parse_html() {
+        let html = parse_html(PAGE).unwrap();
+        let recipe = &html.recipe;
+
+        let source = |range: Range<usize>| -> String {
+            PAGE.chars().skip(range.start).take(range.len()).collect()
+        };
+        assert_eq!(
+            recipe
+                .tokens
+                .iter()
+                .map(|token| source(token.full_range()))
+                .collect::<Vec<_>>(),
+            vec![
+                "1&frac12; cups",
+                "2</span> <span class=\"unit\">tbsp",
+                "3&nbsp;large eggs",
+                "350&deg;F",
+                "20 minutes",
+                "2</b> tsp"
+            ]
+        );
+        assert!(recipe
+            .tokens
+            .iter()
+            .all(|token| source(token.full_range()) == token.raw));
+        assert_eq!(
+            recipe.tokens[0].measure,
+            Measure::single(Rational32::new(3, 2), Unit::Cup)
+        );
+        assert_eq!(recipe.tokens[3].measure.main_unit(), &Unit::Fahrenheit);
+        assert_eq!(recipe.yields.as_ref().unwrap().amount, 4.into());
+
+        // scaling keeps the markup around the measures
+        let scaled = recipe.scale(2).text;
+        assert!(scaled.contains("<li>3 cups flour</li>"));
+        assert!(scaled.contains("<script>var cups = \"4 cups\";</script>"));
+    }
+
+    #[test]
+    fn test_annotated() {
+        let annotated = parse_html(PAGE).unwrap().annotated();
+
+        assert!(annotated.contains(
+            r#"<li><data class="measure" value="360" data-unit="milliliter" data-dimension="volume">1&frac12; cups</data> flour</li>"#
+        ));
+        assert!(annotated.contains(
+            r#"<li><data class="measure" value="30" data-unit="milliliter" data-dimension="volume"><span class="qty">2</span> <span class="unit">tbsp</span></data> sugar</li>"#
+        ));
+        assert!(annotated.contains(
+            r#"<data class="measure" value="3" data-unit="eggs" data-dimension="unitless">3&nbsp;large eggs</data>"#
+        ));
+        assert!(annotated.contains(
+            r#"<b><data class="measure" value="176.67" data-unit="celsius" data-dimension="temperature">350&deg;F</data></b>"#
+        ));
+        assert!(annotated.contains(
+            r#"<p>Sprinkle with <data class="measure" value="10" data-unit="milliliter" data-dimension="volume"><b>2</b> tsp</data> sugar.</p>"#
+        ));
+        // everything else is left alone
+        let stripped = annotated
+            .replace("</data>", "")
+            .split("<data ")
+            .enumerate()
+            .map(|(i, piece)| match piece.split_once('>') {
+                Some((_, rest)) if i > 0 => rest,
+                _ => piece,
+            })
+            .collect::<String>();
+        assert_eq!(stripped, PAGE);
+
+        // too big to write in milliliters
+        let huge = "<p>100000 gallons</p>";
+        assert_eq!(parse_html(huge).unwrap().annotated(), huge);
+    }
+}
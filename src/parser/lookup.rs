@@ -0,0 +1,138 @@
+use std::cmp::Ordering;
+use std::ops::Range;
+
+use crate::parser::{MeasureToken, Position, Recipe};
+
+/// A place in a recipe's text, counted however the caller counts
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TextOffset {
+    Byte(usize),
+    Char(usize),
+    /// UTF-16 code units, the way JavaScript counts
+    Utf16(usize),
+    /// A line and the chars since its start
+    LineColumn {
+        line: usize,
+        column: usize,
+    },
+    /// A line and the UTF-16 code units since its start, the way the language server protocol
+    /// counts
+    LineUtf16 {
+        line: usize,
+        utf16_column: usize,
+    },
+}
+
+impl TextOffset {
+    /// Where `position` is compared to us
+    fn cmp_position(&self, position: &Position) -> Ordering {
+        match *self {
+            TextOffset::Byte(byte) => position.byte.cmp(&byte),
+            TextOffset::Char(char) => position.char.cmp(&char),
+            TextOffset::Utf16(utf16) => position.utf16.cmp(&utf16),
+            TextOffset::LineColumn { line, column } => {
+                (position.line, position.column).cmp(&(line, column))
+            }
+            TextOffset::LineUtf16 { line, utf16_column } => {
+                (position.line, position.utf16_column).cmp(&(line, utf16_column))
+            }
+        }
+    }
+}
+
+impl<'a> Recipe<'a> {
+    /// The measure under `offset`, if there is one
+    pub fn token_at(&self, offset: TextOffset) -> Option<&MeasureToken<'a>> {
+        let after = self
+            .tokens
+            .partition_point(|token| offset.cmp_position(&token.start).is_le());
+        let token = &self.tokens[after.checked_sub(1)?];
+
+        offset
+            .cmp_position(&token.positions().end)
+            .is_gt()
+            .then_some(token)
+    }
+
+    /// The measures that overlap `range`
+    pub fn tokens_in(&self, range: Range<TextOffset>) -> &[MeasureToken<'a>] {
+        // tokens don't overlap, so they're sorted by where they end as well as where they start
+        let first = self
+            .tokens
+            .partition_point(|token| range.start.cmp_position(&token.positions().end).is_le());
+        let last = self
+            .tokens
+            .partition_point(|token| range.end.cmp_position(&token.start).is_lt());
+
+        &self.tokens[first..last.max(first)]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::parse_recipe;
+
+    use super::*;
+
+    #[test]
+    fn test_token_at() {
+        // `🥚` is 4 bytes and 2 UTF-16 code units
+        let recipe = parse_recipe("🥚 2 eggs\nWhisk ½ cup milk and 1 tbsp sugar").unwrap();
+        let raw = |token: Option<&MeasureToken>| token.map(|token| token.raw.to_string());
+
+        assert_eq!(
+            raw(recipe.token_at(TextOffset::Byte(5))),
+            Some("2 eggs".into())
+        );
+        assert_eq!(
+            raw(recipe.token_at(TextOffset::Char(2))),
+            Some("2 eggs".into())
+        );
+        assert_eq!(
+            raw(recipe.token_at(TextOffset::Utf16(3))),
+            Some("2 eggs".into())
+        );
+        assert_eq!(raw(recipe.token_at(TextOffset::Char(8))), None);
+        assert_eq!(raw(recipe.token_at(TextOffset::Char(1))), None);
+        assert_eq!(
+            raw(recipe.token_at(TextOffset::LineColumn { line: 1, column: 8 })),
+            Some("½ cup".into())
+        );
+        assert_eq!(
+            raw(recipe.token_at(TextOffset::LineUtf16 {
+                line: 1,
+                utf16_column: 11
+            })),
+            None
+        );
+    }
+
+    #[test]
+    fn test_tokens_in() {
+        let recipe = parse_recipe("🥚 2 eggs\nWhisk ½ cup milk and 1 tbsp sugar").unwrap();
+        let raws = |range: Range<TextOffset>| {
+            recipe
+                .tokens_in(range)
+                .iter()
+                .map(|token| token.raw.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            raws(TextOffset::Char(7)..TextOffset::Char(17)),
+            vec!["2 eggs", "½ cup"]
+        );
+        assert_eq!(
+            raws(TextOffset::LineColumn { line: 1, column: 9 }..TextOffset::Byte(100)),
+            vec!["½ cup", "1 tbsp"]
+        );
+        assert_eq!(
+            raws(TextOffset::Char(8)..TextOffset::Char(15)),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            raws(TextOffset::Char(20)..TextOffset::Char(4)),
+            Vec::<String>::new()
+        );
+    }
+}
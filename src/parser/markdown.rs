@@ -0,0 +1,347 @@
+use std::ops::Range;
+
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+
+use crate::parser::parse_yield::find_yield;
+use crate::parser::structure::{trim, Builder};
+use crate::parser::{tokenize, ParseOptions, ParserInput, PositionCursor, Recipe, Span, Structure};
+use crate::ParseError;
+
+/// A recipe written in Markdown. Only prose and list items are searched for measures, so numbers
+/// in code, link URLs and image alt text are left alone, but every range still points into the
+/// original Markdown.
+pub struct MarkdownRecipe<'a> {
+    pub recipe: Recipe<'a>,
+    /// The headings, list items and paragraphs of the document, in order
+    pub blocks: Vec<MarkdownBlock<'a>>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MarkdownBlock<'a> {
+    pub kind: BlockKind,
+    /// The block's text without its markup (`#`, `-`, `1.`)
+    pub text: &'a str,
+    pub span: Span,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum BlockKind {
+    /// A heading and its level, 1 for `#`
+    Heading(u8),
+    /// An item in a list, with its number if the list is ordered
+    ListItem(Option<u32>),
+    Paragraph,
+}
+
+pub fn parse_markdown(input: &str) -> Result<MarkdownRecipe<'_>, ParseError> {
+    parse_markdown_with(input, &ParseOptions::default())
+}
+
+pub fn parse_markdown_with<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> Result<MarkdownRecipe<'a>, ParseError> {
+    let mut walker = Walker::default();
+    for (event, range) in Parser::new(input).into_offset_iter() {
+        walker.event(event, range);
+    }
+
+    let mut positions = PositionCursor::new(input);
+    let blocks = walker
+        .blocks
+        .into_iter()
+        .filter_map(|(kind, range)| {
+            let mut block = ParserInput::new(&input[range.clone()], positions.at(range.start));
+            if let BlockKind::Heading(_) = kind {
+                block = ParserInput::new(block.input.trim_end_matches(':'), block.position);
+            }
+            let block = trim(block)?;
+            Some(MarkdownBlock {
+                kind,
+                text: block.input,
+                span: block.span(),
+            })
+        })
+        .collect();
+
+    let (mut tokens, mut warnings, mut yields) = (vec![], vec![], None);
+    for range in walker.prose {
+        let prose = ParserInput::new(&input[range.clone()], positions.at(range.start));
+        let (found, skipped) = tokenize(prose, options)?;
+        tokens.extend(found);
+        warnings.extend(skipped);
+        yields = yields.or_else(|| find_yield(prose));
+    }
+
+    Ok(MarkdownRecipe {
+        recipe: Recipe::new(input, tokens, warnings, yields),
+        blocks,
+    })
+}
+
+impl<'a> MarkdownRecipe<'a> {
+    /// Split the recipe into a title, groups of ingredients and steps, using the document's
+    /// headings and lists
+    pub fn structure(&self) -> Structure<'_, 'a> {
+        let mut builder = Builder::new(&self.recipe);
+        let mut positions = PositionCursor::new(self.recipe.raw);
+        for block in &self.blocks {
+            let input = ParserInput::new(block.text, positions.at(block.span.bytes.start));
+            match block.kind {
+                BlockKind::Heading(_) => builder.heading(input, true),
+                BlockKind::ListItem(Some(number)) => builder.numbered(number, input),
+                BlockKind::ListItem(None) => builder.bullet(input),
+                BlockKind::Paragraph => {
+                    // a paragraph can still have lines like `For the sauce:`
+                    let mut offset = 0;
+                    for line in block.text.split_inclusive('\n') {
+                        let rest = input.advance(offset);
+                        let content = line.trim_end_matches(['\n', '\r']);
+                        let line_input = ParserInput::new(content, rest.position);
+                        if let Some(trimmed) = trim(line_input) {
+                            builder.line(trimmed, offset == 0);
+                        }
+                        offset += line.len();
+                    }
+                }
+            }
+        }
+
+        builder.build()
+    }
+}
+
+/// A block we're in the middle of
+struct Open {
+    kind: BlockKind,
+    /// The bytes of the block's content so far
+    content: Option<Range<usize>>,
+    /// An item with a list inside it is finished as soon as that list starts
+    finished: bool,
+}
+
+#[derive(Default)]
+struct Walker {
+    open: Vec<Open>,
+    /// The next number for each ordered list we're in, [`None`] for bulleted lists
+    lists: Vec<Option<u32>>,
+    /// How deep we are in markup whose text isn't prose (code blocks, images, HTML)
+    skipping: usize,
+    blocks: Vec<(BlockKind, Range<usize>)>,
+    /// The bytes of each run of prose
+    prose: Vec<Range<usize>>,
+}
+
+impl Walker {
+    fn event(&mut self, event: Event, range: Range<usize>) {
+        match event {
+            Event::Start(Tag::CodeBlock(_) | Tag::HtmlBlock | Tag::MetadataBlock(_)) => {
+                self.skipping += 1
+            }
+            Event::End(TagEnd::CodeBlock | TagEnd::HtmlBlock | TagEnd::MetadataBlock(_)) => {
+                self.skipping -= 1
+            }
+            Event::Start(Tag::Image { .. }) => {
+                self.content(range);
+                self.skipping += 1;
+            }
+            Event::End(TagEnd::Image) => self.skipping -= 1,
+            _ if self.skipping > 0 => {}
+
+            Event::Start(Tag::Heading { level, .. }) => self.open(BlockKind::Heading(level as u8)),
+            Event::Start(Tag::Paragraph) => self.open(BlockKind::Paragraph),
+            Event::Start(Tag::List(start)) => {
+                if let Some(item) = self.open.last_mut().filter(|open| !open.finished) {
+                    item.finished = true;
+                    let kind = item.kind;
+                    if let Some(content) = item.content.clone() {
+                        self.blocks.push((kind, content));
+                    }
+                }
+                self.lists
+                    .push(start.map(|start| u32::try_from(start).unwrap_or(u32::MAX)));
+            }
+            Event::End(TagEnd::List(_)) => {
+                self.lists.pop();
+            }
+            Event::Start(Tag::Item) => {
+                let number = self.lists.last_mut().and_then(|next| {
+                    let number = (*next)?;
+                    *next = Some(number.saturating_add(1));
+                    Some(number)
+                });
+                self.open(BlockKind::ListItem(number));
+            }
+            Event::End(TagEnd::Heading(_) | TagEnd::Paragraph | TagEnd::Item) => self.close(),
+
+            Event::Text(_) | Event::SoftBreak | Event::HardBreak => {
+                self.content(range.clone());
+                match self.prose.last_mut() {
+                    Some(prose) if prose.end == range.start => prose.end = range.end,
+                    _ => self.prose.push(range),
+                }
+            }
+            Event::Code(_)
+            | Event::InlineHtml(_)
+            | Event::Html(_)
+            | Event::InlineMath(_)
+            | Event::FootnoteReference(_)
+            | Event::Start(
+                Tag::Emphasis
+                | Tag::Strong
+                | Tag::Strikethrough
+                | Tag::Superscript
+                | Tag::Subscript
+                | Tag::Link { .. },
+            ) => self.content(range),
+            _ => {}
+        }
+    }
+
+    fn open(&mut self, kind: BlockKind) {
+        self.open.push(Open {
+            kind,
+            content: None,
+            finished: false,
+        });
+    }
+
+    fn close(&mut self) {
+        let Some(open) = self.open.pop() else {
+            return;
+        };
+        if open.finished {
+            return;
+        }
+        match (open.content, self.open.last_mut()) {
+            // the paragraphs in a loose list are part of the item
+            (Some(content), Some(parent))
+                if open.kind == BlockKind::Paragraph
+                    && matches!(parent.kind, BlockKind::ListItem(_))
+                    && !parent.finished =>
+            {
+                parent.extend(content)
+            }
+            (Some(content), _) => self.blocks.push((open.kind, content)),
+            (None, _) => {}
+        }
+    }
+
+    fn content(&mut self, range: Range<usize>) {
+        if let Some(open) = self.open.last_mut().filter(|open| !open.finished) {
+            open.extend(range);
+        }
+    }
+}
+
+impl Open {
+    fn extend(&mut self, range: Range<usize>) {
+        self.content = Some(match self.content.take() {
+            Some(content) => content.start.min(range.start)..content.end.max(range.end),
+            None => range,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Unit;
+
+    use super::*;
+
+    const PANCAKES: &str = "# Pancakes
+
+Serves 4. Adapted from [page 2](https://example.com/2-cups) ![3 cups](img/4.png)
+
+## Ingredients
+
+- 1 ½ cups flour
+- 2 tbsp sugar, see `step 3 tbsp`
+
+## Method
+
+1. Whisk the flour and sugar
+   with 1 cup milk.
+2. Cook for 2 minutes a side.
+
+```
+10 cups of code
+```
+";
+
+    #[test]
+    fn test_parse_markdown() {
+        let markdown = parse_markdown(PANCAKES).unwrap();
+        let recipe = &markdown.recipe;
+
+        // ranges point into the Markdown
+        let source = |range: Range<usize>| -> String {
+            PANCAKES
+                .chars()
+                .skip(range.start)
+                .take(range.len())
+                .collect()
+        };
+        assert_eq!(
+            recipe
+                .tokens
+                .iter()
+                .map(|token| source(token.full_range()))
+                .collect::<Vec<_>>(),
+            vec!["1 ½ cups", "2 tbsp", "1 cup", "2 minutes"]
+        );
+        assert!(recipe
+            .tokens
+            .iter()
+            .all(|token| source(token.full_range()) == token.raw));
+        assert_eq!(recipe.yields.as_ref().unwrap().amount, 4.into());
+
+        assert_eq!(
+            markdown
+                .blocks
+                .iter()
+                .map(|block| (block.kind, block.text))
+                .collect::<Vec<_>>(),
+            vec![
+                (BlockKind::Heading(1), "Pancakes"),
+                (
+                    BlockKind::Paragraph,
+                    "Serves 4. Adapted from [page 2](https://example.com/2-cups) ![3 cups](img/4.png)"
+                ),
+                (BlockKind::Heading(2), "Ingredients"),
+                (BlockKind::ListItem(None), "1 ½ cups flour"),
+                (BlockKind::ListItem(None), "2 tbsp sugar, see `step 3 tbsp`"),
+                (BlockKind::Heading(2), "Method"),
+                (
+                    BlockKind::ListItem(Some(1)),
+                    "Whisk the flour and sugar\n   with 1 cup milk."
+                ),
+                (BlockKind::ListItem(Some(2)), "Cook for 2 minutes a side."),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_markdown_structure() {
+        let markdown = parse_markdown(PANCAKES).unwrap();
+        let structure = markdown.structure();
+
+        assert_eq!(structure.title.unwrap().text, "Pancakes");
+        assert_eq!(structure.ingredient_groups.len(), 1);
+        assert_eq!(structure.ingredient_groups[0].lines.len(), 2);
+        assert_eq!(
+            structure.ingredient_groups[0].lines[1].tokens[0]
+                .measure
+                .main_unit(),
+            &Unit::Tablespoon
+        );
+        assert_eq!(
+            structure
+                .steps
+                .iter()
+                .map(|step| (step.number, step.body.tokens.len()))
+                .collect::<Vec<_>>(),
+            vec![(Some(1), 1), (Some(2), 1)]
+        );
+    }
+}
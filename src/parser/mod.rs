@@ -2,38 +2,79 @@ use std::fmt::{Debug, Formatter};
 use std::ops::{Range, RangeFrom, RangeTo};
 use std::str::{CharIndices, Chars, FromStr};
 
+use nom::error::{ErrorKind, FromExternalError};
 use nom::InputLength;
 use nom::{
     AsBytes, Compare, CompareResult, InputIter, InputTake, Needed, Offset, ParseTo, Slice,
     UnspecializedInput,
 };
 
+use crate::ParseError;
+
 pub use char_indexing::*;
+pub use document::*;
+pub use fuzzy::FuzzyMatch;
+pub use html::*;
+pub use lookup::*;
+pub use markdown::*;
+pub use options::*;
+pub use parse_ingredient::*;
 pub use parse_measure::*;
 pub use parse_recipe::*;
+pub use parse_yield::Yield;
+pub use reparse::*;
+pub use span::*;
+pub use stream::*;
+pub use structure::*;
 
 mod char_indexing;
+mod disambiguate;
+mod document;
+mod fuzzy;
+mod html;
+mod lookup;
+mod markdown;
+mod options;
+mod parse_ingredient;
 mod parse_measure;
 mod parse_recipe;
+mod parse_yield;
+mod render;
+mod reparse;
+mod span;
+mod stream;
+mod structure;
+mod unit_index;
 
 #[derive(Eq, PartialEq, Clone, Copy)]
 pub struct ParserInput<'a> {
-    input: &'a str,
-    /// The index of the chars in the original string, not the bytes
-    char_index: usize,
+    pub(crate) input: &'a str,
+    /// Where `input` starts in the original string
+    pub(crate) position: Position,
 }
 
 impl<'a> ParserInput<'a> {
-    fn new(input: &'a str, char_index: usize) -> Self {
-        Self { input, char_index }
+    pub(crate) fn new(input: &'a str, position: Position) -> Self {
+        Self { input, position }
     }
 
-    fn char_index(&self, byte_offset: usize) -> usize {
-        self.char_index + self.input.char_index_for_byte(byte_offset)
+    pub(crate) fn range(&self) -> Range<usize> {
+        self.position.char..self.position.char + self.input.chars().count()
     }
 
-    fn range(&self) -> Range<usize> {
-        self.char_index..self.char_index + self.input.chars().count()
+    pub(crate) fn span(&self) -> Span {
+        Span::new(
+            self.range(),
+            self.position.byte..self.position.byte + self.input.len(),
+        )
+    }
+
+    /// Drop the first `byte_offset` bytes of our input
+    pub(crate) fn advance(&self, byte_offset: usize) -> Self {
+        Self::new(
+            &self.input[byte_offset..],
+            self.position.advance(&self.input[..byte_offset]),
+        )
     }
 }
 
@@ -71,36 +112,31 @@ impl<'a> InputTake for ParserInput<'a> {
     fn take(&self, count: usize) -> Self {
         Self {
             input: self.input.take(count),
-            char_index: self.char_index,
+            ..*self
         }
     }
 
     fn take_split(&self, count: usize) -> (Self, Self) {
-        let (suffix, prefix) = self.input.take_split(count);
-        (
-            Self::new(suffix, self.char_index(count)),
-            Self::new(prefix, self.char_index),
-        )
+        let (_, prefix) = self.input.take_split(count);
+        (self.advance(count), Self::new(prefix, self.position))
     }
 }
 
 impl<'a> Slice<Range<usize>> for ParserInput<'a> {
     fn slice(&self, range: Range<usize>) -> Self {
-        let index = self.char_index + range.start;
-        Self::new(self.input.slice(range), index)
+        self.advance(range.start).slice(..range.end - range.start)
     }
 }
 
 impl<'a> Slice<RangeFrom<usize>> for ParserInput<'a> {
     fn slice(&self, range: RangeFrom<usize>) -> Self {
-        let index = self.char_index(range.start);
-        Self::new(self.input.slice(range), index)
+        self.advance(range.start)
     }
 }
 
 impl<'a> Slice<RangeTo<usize>> for ParserInput<'a> {
     fn slice(&self, range: RangeTo<usize>) -> Self {
-        Self::new(self.input.slice(range), self.char_index)
+        Self::new(self.input.slice(range), self.position)
     }
 }
 
@@ -144,9 +180,28 @@ impl<'a, 'b> Compare<&'b [u8]> for ParserInput<'a> {
     }
 }
 
+impl<'a> nom::error::ParseError<ParserInput<'a>> for ParseError {
+    fn from_error_kind(input: ParserInput<'a>, kind: ErrorKind) -> Self {
+        ParseError::Unexpected {
+            kind,
+            span: input.take(0).span(),
+        }
+    }
+
+    fn append(_input: ParserInput<'a>, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<'a, E> FromExternalError<ParserInput<'a>, E> for ParseError {
+    fn from_external_error(input: ParserInput<'a>, kind: ErrorKind, _error: E) -> Self {
+        nom::error::ParseError::from_error_kind(input, kind)
+    }
+}
+
 impl<'a> From<&'a str> for ParserInput<'a> {
     fn from(input: &'a str) -> Self {
-        Self::new(input, 0)
+        Self::new(input, Position::default())
     }
 }
 
@@ -158,13 +213,8 @@ impl<'a> From<ParserInput<'a>> for &'a str {
 
 impl<'a> Debug for ParserInput<'a> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}->{}: {}",
-            self.char_index,
-            self.char_index + self.input.len(),
-            self.input
-        )
+        let range = self.range();
+        write!(f, "{}->{}: {}", range.start, range.end, self.input)
     }
 }
 
@@ -172,22 +222,35 @@ impl<'a> Debug for ParserInput<'a> {
 mod test {
     use nom::{IResult, Parser};
 
-    use crate::parser::ParserInput;
+    use nom::Slice;
+
+    use crate::parser::{ParserInput, Span};
 
     #[test]
     fn test_char_offset() {
-        assert_eq!(ParserInput::new("½2", 0).char_index(2), 1);
-        assert_eq!(ParserInput::new("½2", 0).char_index(3), 2);
+        assert_eq!(ParserInput::from("½2").advance(2).position.char, 1);
+        assert_eq!(ParserInput::from("½2").advance(3).position.char, 2);
+        assert_eq!(ParserInput::from("½2").advance(3).position.utf16, 2);
     }
 
     #[test]
     fn test_range() {
-        assert_eq!(ParserInput::new("½2", 0).range(), 0..2);
-        assert_eq!(ParserInput::new("12", 0).range(), 0..2);
-        assert_eq!(ParserInput::new("½½", 0).range(), 0..2);
-        assert_eq!(ParserInput::new("1½1", 0).range(), 0..3);
-        assert_eq!(ParserInput::new("1½1", 1).range(), 1..4);
-        assert_eq!(ParserInput::new("1½1", 2).range(), 2..5);
+        assert_eq!(ParserInput::from("½2").range(), 0..2);
+        assert_eq!(ParserInput::from("12").range(), 0..2);
+        assert_eq!(ParserInput::from("½½").range(), 0..2);
+        assert_eq!(ParserInput::from("1½1").range(), 0..3);
+        assert_eq!(ParserInput::from("01½1").advance(1).range(), 1..4);
+        assert_eq!(ParserInput::from("001½1").advance(2).range(), 2..5);
+    }
+
+    #[test]
+    fn test_slice() {
+        let input = ParserInput::from("½ cup");
+        assert_eq!(input.slice(2..5), input.advance(2).slice(..3));
+        assert_eq!(input.slice(2..5).input, " cu");
+        assert_eq!(input.slice(3..).position.char, 2);
+        assert_eq!(input.slice(..2), ParserInput::from("½"));
+        assert_eq!(input.slice(3..).span(), Span::new(2..5, 3..6));
     }
 
     /// Drop ParserInput for testing
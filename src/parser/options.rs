@@ -0,0 +1,65 @@
+/// Settings that change what the parser will accept as a measure
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// When set, unit text that doesn't match any unit is matched against every alias, and the
+    /// closest alias within this many edits (`teaspon`, `cupss`) is used instead
+    pub fuzzy_threshold: Option<usize>,
+    /// What to do with numbers followed by a word that isn't a unit we know about
+    pub unit_mode: UnitMode,
+}
+
+/// How to treat a number followed by a word that isn't one of our units (`3 large`, `10 times`)
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub enum UnitMode {
+    /// Any word becomes a [`crate::Unit::Unitless`] unit
+    #[default]
+    Lenient,
+    /// Only these words (compared case insensitively) become [`crate::Unit::Unitless`] units
+    CountNouns(Vec<String>),
+    /// Only units we know about produce measures
+    Strict,
+}
+
+/// Things that are commonly counted rather than measured in recipes
+pub const COMMON_COUNT_NOUNS: [&str; 24] = [
+    "egg", "eggs", "clove", "cloves", "can", "cans", "slice", "slices", "piece", "pieces", "stick",
+    "sticks", "sprig", "sprigs", "bunch", "bunches", "head", "heads", "leaf", "leaves", "package",
+    "packages", "fillet", "fillets",
+];
+
+impl ParseOptions {
+    pub fn fuzzy(threshold: usize) -> ParseOptions {
+        ParseOptions {
+            fuzzy_threshold: Some(threshold),
+            ..Default::default()
+        }
+    }
+
+    pub fn strict() -> ParseOptions {
+        ParseOptions {
+            unit_mode: UnitMode::Strict,
+            ..Default::default()
+        }
+    }
+
+    /// Only allow unitless measures of [`COMMON_COUNT_NOUNS`]
+    pub fn count_nouns() -> ParseOptions {
+        ParseOptions {
+            unit_mode: UnitMode::CountNouns(
+                COMMON_COUNT_NOUNS.iter().map(ToString::to_string).collect(),
+            ),
+            ..Default::default()
+        }
+    }
+}
+
+impl UnitMode {
+    /// Should `unit` be allowed as a [`crate::Unit::Unitless`] unit
+    pub fn allows_unitless(&self, unit: &str) -> bool {
+        match self {
+            UnitMode::Lenient => true,
+            UnitMode::CountNouns(nouns) => nouns.iter().any(|noun| noun.eq_ignore_ascii_case(unit)),
+            UnitMode::Strict => false,
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use crate::parser::{parse_measure_with, MeasureToken, ParseOptions, ParserInput};
+use crate::{ParseError, Unit};
+
+/// Phrases at the end of a line that describe how much to use rather than what the ingredient is
+const NOTE_PHRASES: [&str; 5] = [
+    "to taste",
+    "as needed",
+    "for garnish",
+    "for serving",
+    "optional",
+];
+
+/// A line from an ingredients list, like `2 cups all-purpose flour, sifted`
+#[derive(Debug, Eq, PartialEq)]
+pub struct Ingredient<'a> {
+    /// The measure at the start of the line, [`None`] for lines like `salt to taste`
+    pub measure: Option<MeasureToken<'a>>,
+    pub name: &'a str,
+    /// How to prepare the ingredient or how much to use (`sifted`, `to taste`)
+    pub notes: Option<&'a str>,
+}
+
+pub fn parse_ingredient_line(line: &str) -> Result<Ingredient<'_>, ParseError> {
+    parse_ingredient_line_with(line, &ParseOptions::default())
+}
+
+pub fn parse_ingredient_line_with<'a>(
+    line: &'a str,
+    options: &ParseOptions,
+) -> Result<Ingredient<'a>, ParseError> {
+    // skip list markers and indentation
+    let start = line.len()
+        - line
+            .trim_start_matches(|c: char| c.is_whitespace() || matches!(c, '-' | '*' | '•'))
+            .len();
+    let input = ParserInput::from(line).advance(start);
+
+    let (rest, measure) = match parse_measure_with(options)(input) {
+        Ok((remainder, token)) => (remainder.input, Some(token)),
+        Err(nom::Err::Failure(error)) => return Err(error),
+        Err(_) => (input.input, None),
+    };
+
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix("of ").unwrap_or(rest);
+    let (name, notes) = split_notes(rest.trim_end());
+
+    // `3 large eggs` has its name in the unit
+    let name = match &measure {
+        Some(token) if name.is_empty() => match token.measure.main_unit() {
+            Unit::Unitless { .. } => unit_text(line, token),
+            _ => name,
+        },
+        _ => name,
+    };
+
+    Ok(Ingredient {
+        measure,
+        name,
+        notes,
+    })
+}
+
+fn split_notes(text: &str) -> (&str, Option<&str>) {
+    if let Some((name, notes)) = text.split_once(',') {
+        return (
+            name.trim_end(),
+            Some(notes.trim()).filter(|notes| !notes.is_empty()),
+        );
+    }
+
+    // the phrases are ASCII, so matching them doesn't need to lowercase (and resize) the text
+    for phrase in NOTE_PHRASES {
+        let split = text.len().saturating_sub(phrase.len());
+        match text.get(split..) {
+            Some(notes) if notes.eq_ignore_ascii_case(phrase) => {
+                return (text[..split].trim_end(), Some(notes));
+            }
+            _ => {}
+        }
+    }
+
+    (text, None)
+}
+
+/// The unit text of `token` as a slice of `line`, so it lives as long as the line does
+fn unit_text<'a>(line: &'a str, token: &MeasureToken) -> &'a str {
+    let mut chars = line.char_indices().map(|(i, _)| i).chain([line.len()]);
+    let start = chars.nth(token.unit_range.start).unwrap_or(line.len());
+    let end = chars
+        .nth(token.unit_range.len().saturating_sub(1))
+        .unwrap_or(line.len());
+
+    &line[start..end]
+}
+
+#[cfg(test)]
+mod test {
+    use num_rational::Rational32;
+
+    use crate::Measure;
+
+    use super::*;
+
+    fn parts(line: &str) -> (Option<Measure>, &str, Option<&str>) {
+        let ingredient = parse_ingredient_line(line).unwrap();
+        (
+            ingredient.measure.map(|token| token.measure),
+            ingredient.name,
+            ingredient.notes,
+        )
+    }
+
+    #[test]
+    fn test_parse_ingredient_line() {
+        assert_eq!(
+            parts("2 cups all-purpose flour, sifted"),
+            (
+                Some(Measure::single(Rational32::from_integer(2), Unit::Cup)),
+                "all-purpose flour",
+                Some("sifted")
+            )
+        );
+        assert_eq!(parts("salt to taste"), (None, "salt", Some("to taste")));
+        assert_eq!(
+            parts("- ½ cup of sugar"),
+            (
+                Some(Measure::single(Rational32::new(1, 2), Unit::Cup)),
+                "sugar",
+                None
+            )
+        );
+        assert_eq!(
+            parts("3 large eggs"),
+            (
+                Some(Measure::single(
+                    Rational32::from_integer(3),
+                    Unit::unitless("eggs".to_string())
+                )),
+                "eggs",
+                None
+            )
+        );
+        assert_eq!(
+            parts("2 cloves garlic, minced"),
+            (
+                Some(Measure::single(
+                    Rational32::from_integer(2),
+                    Unit::unitless("cloves".to_string())
+                )),
+                "garlic",
+                Some("minced")
+            )
+        );
+        assert_eq!(
+            parts("Fresh parsley, for garnish"),
+            (None, "Fresh parsley", Some("for garnish"))
+        );
+
+        assert_eq!(
+            parts("2 cups Ⱥpple To Taste"),
+            (
+                Some(Measure::single(Rational32::from_integer(2), Unit::Cup)),
+                "Ⱥpple",
+                Some("To Taste")
+            )
+        );
+        assert_eq!(parts("1 cup ẞéoptional").1, "ẞé");
+
+        let ingredient = parse_ingredient_line("  * 1 tsp salt").unwrap();
+        assert_eq!(ingredient.measure.unwrap().full_range(), 4..9);
+
+        assert!(matches!(
+            parse_ingredient_line("1/0 cup flour"),
+            Err(ParseError::InfiniteNumber { .. })
+        ));
+    }
+}
@@ -1,101 +1,214 @@
 use std::borrow::Cow;
 use std::fmt::{Debug, Formatter};
-use std::num::ParseIntError;
 use std::ops::Range;
-use std::primitive;
 
 use nom::branch::alt;
-use nom::character::complete::{alpha1, char, digit1, multispace0, one_of, u32};
-use nom::combinator::{consumed, map, map_res, value};
-use nom::sequence::{separated_pair, tuple};
+use nom::bytes::complete::tag_no_case;
+use nom::character::complete::{alpha1, char, digit1, multispace0, one_of, satisfy, space1};
+use nom::combinator::{consumed, map, not, opt, recognize, value, verify};
+use nom::error::ErrorKind;
+use nom::multi::many0;
+use nom::sequence::{pair, preceded, separated_pair, terminated, tuple};
 use nom::IResult;
 use nom::InputLength;
 use num_rational::Rational32;
+use num_traits::CheckedAdd;
 use serde::{Deserialize, Serialize};
 
-use crate::parser::{CharIndexing, ParserInput};
-use crate::{Measure, ParseError, Unit, UNITFUL_UNITS};
+use crate::parser::fuzzy::fuzzy_unit;
+use crate::parser::unit_index::lookup_unit;
+use crate::parser::{CharIndexing, FuzzyMatch, ParseOptions, ParserInput, Position};
+use crate::{Dimension, Measure, Modifier, ParseError, Unit, MODIFIERS};
 
-#[derive(Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub struct MeasureToken<'a> {
     pub measure: Measure,
+    /// The chars of the number in the recipe
     pub number_range: Range<usize>,
+    /// The chars of the unit in the recipe
     pub unit_range: Range<usize>,
     pub raw: Cow<'a, str>,
+    /// Where the token starts in the recipe, which along with `raw` gives every other position
+    pub start: Position,
+    /// How sure we are that `measure` has the right unit
+    pub confidence: Confidence,
+    /// Other units the unit text could have meant, that we decided against
+    pub alternatives: Vec<Unit>,
+    /// Set when the unit text was misspelled and we picked the unit it was closest to
+    pub fuzzy: Option<FuzzyMatch>,
+    /// Size and descriptor words between the number and the unit (`3 large eggs`)
+    pub modifiers: Vec<Modifier>,
 }
 
-pub fn parse_measure(input: ParserInput) -> IResult<ParserInput, MeasureToken> {
-    let (remainder, ((number_raw, number), _, (unit_raw, unit))) = alt((
-        tuple((consumed(parse_integer), multispace0, consumed(parse_unit))),
-        tuple((consumed(parse_decimal), multispace0, consumed(parse_unit))),
-        tuple((consumed(parse_rational), multispace0, consumed(parse_unit))),
-    ))(input)?;
+#[derive(Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Debug, Copy, Clone, Default)]
+pub enum Confidence {
+    /// The unit text matched several units and nothing in the context helped us pick one
+    Guess,
+    /// The unit text matched several units but the context points to one of them
+    Likely,
+    /// The unit text only matches a single unit
+    #[default]
+    Certain,
+}
 
-    Ok((
-        remainder,
-        MeasureToken {
-            measure: Measure::single(number, unit),
-            number_range: number_raw.range(),
-            unit_range: unit_raw.range(),
-            raw: Cow::Borrowed(&input.input[..input.input_len() - remainder.input_len()]),
-        },
-    ))
+pub fn parse_measure(input: ParserInput) -> IResult<ParserInput, MeasureToken, ParseError> {
+    parse_measure_with(&ParseOptions::default())(input)
+}
+
+/// Parse a measure, failing with [`nom::Err::Failure`] if we find something that is clearly
+/// meant to be a measure but is malformed (`1/0 cups`)
+pub fn parse_measure_with<'a>(
+    options: &ParseOptions,
+) -> impl Fn(ParserInput<'a>) -> IResult<ParserInput<'a>, MeasureToken<'a>, ParseError> + '_ {
+    move |input: ParserInput<'a>| {
+        let (remainder, ((number_raw, number), _, modifiers, (unit_raw, mut units))) =
+            alt((
+                tuple((
+                    consumed(parse_integer),
+                    multispace0,
+                    parse_modifiers,
+                    consumed(parse_unit),
+                )),
+                tuple((
+                    consumed(parse_decimal),
+                    multispace0,
+                    parse_modifiers,
+                    consumed(parse_unit),
+                )),
+                tuple((
+                    consumed(parse_rational),
+                    multispace0,
+                    parse_modifiers,
+                    consumed(parse_unit),
+                )),
+            ))(input)?;
+
+        let mut unit = units.remove(0);
+        let confidence = if units.is_empty() {
+            Confidence::Certain
+        } else {
+            Confidence::Guess
+        };
+
+        let mut fuzzy = None;
+        if let (Unit::Unitless { unit: unit_text }, Some(threshold)) =
+            (&unit, options.fuzzy_threshold)
+        {
+            if let Some((corrected, fuzzy_match)) = fuzzy_unit(unit_text, threshold) {
+                unit = corrected;
+                fuzzy = Some(fuzzy_match);
+            }
+        }
+
+        if let Unit::Unitless { unit: unit_text } = &unit {
+            if !options.unit_mode.allows_unitless(unit_text) {
+                return Err(nom::Err::Error(ParseError::UnknownUnit {
+                    unit: unit_text.clone(),
+                    span: unit_raw.span(),
+                }));
+            }
+        }
+
+        Ok((
+            remainder,
+            MeasureToken {
+                measure: Measure::single(number, unit),
+                number_range: number_raw.range(),
+                unit_range: unit_raw.range(),
+                raw: Cow::Borrowed(&input.input[..input.input_len() - remainder.input_len()]),
+                start: number_raw.position,
+                confidence,
+                alternatives: units,
+                fuzzy,
+                modifiers,
+            },
+        ))
+    }
 }
 
-fn parse_integer(input: ParserInput) -> IResult<ParserInput, Rational32> {
-    map(u32, |i| Rational32::from_integer(i as i32))(input)
+/// Parse a number written any way a measure's can be (`2`, `1.5`, `1 ½`)
+pub(crate) fn parse_number(input: ParserInput) -> IResult<ParserInput, Rational32, ParseError> {
+    alt((parse_rational, parse_decimal, parse_integer))(input)
 }
 
-fn parse_decimal(input: ParserInput) -> IResult<ParserInput, Rational32> {
-    map_res(
-        separated_pair(
-            u32::<ParserInput, _>,
-            tuple((multispace0, char('.'), multispace0)),
-            digit1,
-        ),
-        |(integer, fraction)| {
-            let parsed_fraction = fraction.input.parse::<primitive::u32>()?;
-            let fraction_rational = Rational32::new(
-                parsed_fraction as i32,
-                10_i32.pow(fraction.input.len() as u32),
-            );
-
-            Ok::<_, ParseIntError>(Rational32::from_integer(integer as i32) + fraction_rational)
-        },
-    )(input)
+fn parse_integer(input: ParserInput) -> IResult<ParserInput, Rational32, ParseError> {
+    map(parse_digits, Rational32::from_integer)(input)
 }
 
-fn parse_rational(input: ParserInput) -> IResult<ParserInput, Rational32> {
+/// Parse a run of digits, failing if they don't fit in our numbers
+pub(crate) fn parse_digits(input: ParserInput) -> IResult<ParserInput, i32, ParseError> {
+    let (remainder, digits) = digit1(input)?;
+    match digits.input.parse() {
+        Ok(number) => Ok((remainder, number)),
+        Err(_) => Err(nom::Err::Failure(ParseError::NumberTooLarge {
+            span: digits.span(),
+        })),
+    }
+}
+
+fn parse_decimal(input: ParserInput) -> IResult<ParserInput, Rational32, ParseError> {
+    let (remainder, (raw, (integer, fraction))) = consumed(separated_pair(
+        parse_digits,
+        tuple((multispace0, char('.'), multispace0)),
+        digit1,
+    ))(input)?;
+
+    let number = 10_i32
+        .checked_pow(fraction.input.len() as u32)
+        .zip(fraction.input.parse::<i32>().ok())
+        .and_then(|(denom, numer)| {
+            Rational32::from_integer(integer).checked_add(&Rational32::new(numer, denom))
+        })
+        .ok_or_else(|| nom::Err::Failure(ParseError::NumberTooLarge { span: raw.span() }))?;
+
+    Ok((remainder, number))
+}
+
+fn parse_rational(input: ParserInput) -> IResult<ParserInput, Rational32, ParseError> {
     alt((multi_rational, simple_rational))(input)
 }
 
 /// Parse something of the form `<rational>`
-fn simple_rational(input: ParserInput) -> IResult<ParserInput, Rational32> {
+fn simple_rational(input: ParserInput) -> IResult<ParserInput, Rational32, ParseError> {
     alt((ascii_rational, unicode_rational))(input)
 }
 
 /// Parse something of the form `<real> <rational>`
-fn multi_rational(input: ParserInput) -> IResult<ParserInput, Rational32> {
-    map(
-        separated_pair(u32, multispace0, simple_rational),
-        |(integer, rational)| Rational32::from_integer(integer as i32) + rational,
-    )(input)
+fn multi_rational(input: ParserInput) -> IResult<ParserInput, Rational32, ParseError> {
+    let (remainder, (raw, (integer, rational))) =
+        consumed(separated_pair(parse_digits, multispace0, simple_rational))(input)?;
+
+    let number = Rational32::from_integer(integer)
+        .checked_add(&rational)
+        .ok_or_else(|| nom::Err::Failure(ParseError::NumberTooLarge { span: raw.span() }))?;
+
+    Ok((remainder, number))
 }
 
-fn ascii_rational(input: ParserInput) -> IResult<ParserInput, Rational32> {
-    map_res(
-        separated_pair(u32, tuple((multispace0, one_of("/⁄"), multispace0)), u32),
-        |(numer, denom)| {
-            if denom != 0 {
-                Ok(Rational32::new(numer as i32, denom as i32))
-            } else {
-                Err(ParseError::InfiniteNumber)
-            }
-        },
-    )(input)
+fn ascii_rational(input: ParserInput) -> IResult<ParserInput, Rational32, ParseError> {
+    let (remainder, (raw, (numer, denom))) = consumed(separated_pair(
+        parse_digits,
+        tuple((multispace0, one_of("/⁄"), multispace0)),
+        parse_digits,
+    ))(input)?;
+
+    if denom != 0 {
+        Ok((remainder, Rational32::new(numer, denom)))
+    } else {
+        Err(nom::Err::Failure(ParseError::InfiniteNumber {
+            span: raw.span(),
+        }))
+    }
 }
 
-fn unicode_rational(input: ParserInput) -> IResult<ParserInput, Rational32> {
+/// Whether `c` could be the first char of a number, every measure starts with one
+pub(crate) fn starts_number(c: char) -> bool {
+    c.is_ascii_digit() || UNICODE_FRACTIONS.contains(c)
+}
+
+const UNICODE_FRACTIONS: &str = "¼½¾⅐⅑⅒⅓⅔⅕⅖⅗⅘⅙⅚⅛⅜⅝⅞";
+
+fn unicode_rational(input: ParserInput) -> IResult<ParserInput, Rational32, ParseError> {
     alt((
         // https://en.wikipedia.org/wiki/Latin-1_Supplement
         value(Rational32::new(1, 4), char('¼')),
@@ -120,50 +233,108 @@ fn unicode_rational(input: ParserInput) -> IResult<ParserInput, Rational32> {
     ))(input)
 }
 
-fn parse_unit(input: ParserInput) -> IResult<ParserInput, Unit> {
-    let (remainder, raw_unit) = alpha1(input)?;
-
-    let mut secondary = None;
-    for unit in UNITFUL_UNITS.iter() {
-        for &alias in unit.aliases() {
-            if raw_unit.input == alias {
-                // if we have an exact match return immediately
-                return Ok((remainder, unit.clone()));
-            } else if raw_unit.input.to_lowercase() == alias.to_lowercase() {
-                // only use a secondary match if we never find an exact match (for cases like t & T)
-                secondary = Some(unit.clone())
+/// Parse a unit, returning every unit it could refer to with the most likely one first. This is
+/// never empty, if we don't recognize the unit we get back a single [`Unit::Unitless`].
+fn parse_unit(input: ParserInput) -> IResult<ParserInput, Vec<Unit>, ParseError> {
+    alt((qualified_degrees, map(unit_word, unit_candidates)))(input)
+}
+
+/// Parse `degrees F` or `degrees Celsius`, where the trailing word tells us exactly which unit
+/// we're dealing with
+fn qualified_degrees(input: ParserInput) -> IResult<ParserInput, Vec<Unit>, ParseError> {
+    map(
+        preceded(
+            verify(unit_word, |word: &ParserInput| {
+                let units = lookup_unit(word.input).unwrap_or_default();
+                units.len() > 1
+                    && units
+                        .iter()
+                        .all(|unit| unit.dimension() == Dimension::Temperature)
+            }),
+            preceded(
+                space1,
+                verify(map(unit_word, unit_candidates), |units: &Vec<Unit>| {
+                    units
+                        .iter()
+                        .any(|unit| unit.dimension() == Dimension::Temperature)
+                }),
+            ),
+        ),
+        |units| {
+            units
+                .into_iter()
+                .filter(|unit| unit.dimension() == Dimension::Temperature)
+                .collect()
+        },
+    )(input)
+}
+
+/// Parse any modifiers that are followed by another word on the same line, so `3 large` (and
+/// `3 large\neggs`) still treats `large` as the unit
+fn parse_modifiers(input: ParserInput) -> IResult<ParserInput, Vec<Modifier>, ParseError> {
+    many0(terminated(parse_modifier, space1))(input)
+}
+
+fn parse_modifier(input: ParserInput) -> IResult<ParserInput, Modifier, ParseError> {
+    for modifier in MODIFIERS.iter() {
+        for &alias in modifier.aliases() {
+            let matched: IResult<_, _, ParseError> = terminated(
+                tag_no_case(alias),
+                not(satisfy(|c| c.is_alphanumeric() || c == '-')),
+            )(input);
+            if let Ok((remainder, _)) = matched {
+                return Ok((remainder, *modifier));
             }
         }
     }
 
-    Ok((
-        remainder,
-        secondary.unwrap_or(Unit::unitless(raw_unit.input.to_string())),
-    ))
+    Err(nom::Err::Error(nom::error::ParseError::from_error_kind(
+        input,
+        ErrorKind::Tag,
+    )))
+}
+
+fn unit_word(input: ParserInput) -> IResult<ParserInput, ParserInput, ParseError> {
+    recognize(pair(opt(char('°')), alpha1))(input)
+}
+
+fn unit_candidates<'a, I: Into<&'a str>>(raw_unit: I) -> Vec<Unit> {
+    let raw_unit = raw_unit.into();
+    match lookup_unit(raw_unit) {
+        Some(units) => units.to_vec(),
+        None => vec![Unit::unitless(raw_unit.to_string())],
+    }
 }
 
 impl MeasureToken<'_> {
+    /// Make a token for `raw`, which starts at `start` in the text it was found in. The ranges
+    /// count chars from the start of that text too, so `number_range` starts at `start.char`.
     pub fn new<'a, S: Into<Cow<'a, str>>>(
         measure: Measure,
         number_range: Range<usize>,
         unit_range: Range<usize>,
         raw: S,
+        start: Position,
     ) -> MeasureToken<'a> {
+        debug_assert_eq!(number_range.start, start.char);
         MeasureToken {
             measure,
             number_range,
             unit_range,
             raw: raw.into(),
+            start,
+            confidence: Confidence::Certain,
+            alternatives: vec![],
+            fuzzy: None,
+            modifiers: vec![],
         }
     }
 
     pub fn into_owned(self) -> MeasureToken<'static> {
-        Self::new(
-            self.measure,
-            self.number_range,
-            self.unit_range,
-            self.raw.into_owned(),
-        )
+        MeasureToken {
+            raw: Cow::Owned(self.raw.into_owned()),
+            ..self
+        }
     }
 }
 
@@ -200,6 +371,36 @@ impl<'a> MeasureToken<'a> {
     pub fn text(&self) -> Cow<'a, str> {
         self.raw.clone()
     }
+
+    /// Where the whole token is in the recipe, in bytes, chars, UTF-16 and lines and columns
+    pub fn positions(&self) -> Range<Position> {
+        self.start..self.start.advance(&self.raw)
+    }
+
+    pub fn number_positions(&self) -> Range<Position> {
+        self.start..self.start.advance(&self.number_text())
+    }
+
+    pub fn unit_positions(&self) -> Range<Position> {
+        let before_unit = self
+            .raw
+            .char_slice(0..self.unit_range.start - self.number_range.start)
+            .unwrap_or_default();
+        let start = self.start.advance(&before_unit);
+
+        start..start.advance(&self.unit_text())
+    }
+
+    pub fn byte_range(&self) -> Range<usize> {
+        self.start.byte..self.start.byte + self.raw.len()
+    }
+
+    /// Where the token is in UTF-16 code units, the way JavaScript indexes strings
+    pub fn utf16_range(&self) -> Range<usize> {
+        let Range { start, end } = self.positions();
+
+        start.utf16..end.utf16
+    }
 }
 
 impl<'a> Debug for MeasureToken<'a> {
@@ -221,6 +422,7 @@ impl<'a> Debug for MeasureToken<'a> {
 #[cfg(test)]
 mod test {
     use crate::parser::test::raw;
+    use crate::parser::Span;
 
     use super::*;
 
@@ -234,7 +436,8 @@ mod test {
                     Measure::single(Rational32::new(3, 4), Unit::Teaspoon),
                     0..3,
                     4..12,
-                    "3/4 teaspoon"
+                    "3/4 teaspoon",
+                    Position::default()
                 )
             ))
         );
@@ -246,7 +449,8 @@ mod test {
                     Measure::single(Rational32::new(3, 4), Unit::Tablespoon),
                     0..3,
                     5..15,
-                    "3/4  tablespoon"
+                    "3/4  tablespoon",
+                    Position::default()
                 )
             ))
         );
@@ -258,7 +462,8 @@ mod test {
                     Measure::single(Rational32::new(3, 4), Unit::Teaspoon),
                     0..3,
                     3..11,
-                    "3/4teaspoon"
+                    "3/4teaspoon",
+                    Position::default()
                 )
             ))
         );
@@ -270,7 +475,8 @@ mod test {
                     Measure::single(Rational32::new(3, 4), Unit::Teaspoon),
                     0..4,
                     4..12,
-                    "3 /4teaspoon"
+                    "3 /4teaspoon",
+                    Position::default()
                 )
             ))
         );
@@ -282,7 +488,8 @@ mod test {
                     Measure::single(Rational32::new(3, 4), Unit::Teaspoon),
                     0..4,
                     4..12,
-                    "3/ 4teaspoon"
+                    "3/ 4teaspoon",
+                    Position::default()
                 )
             ))
         );
@@ -294,7 +501,8 @@ mod test {
                     Measure::single(Rational32::new(3, 4), Unit::Teaspoon),
                     0..5,
                     6..14,
-                    "3 / 4 teaspoon"
+                    "3 / 4 teaspoon",
+                    Position::default()
                 )
             ))
         );
@@ -306,7 +514,8 @@ mod test {
                     Measure::single(Rational32::new(7, 4), Unit::Teaspoon),
                     0..7,
                     8..16,
-                    "1 3 / 4 teaspoon"
+                    "1 3 / 4 teaspoon",
+                    Position::default()
                 )
             ))
         );
@@ -318,7 +527,8 @@ mod test {
                     Measure::single(Rational32::new(7, 4), Unit::Teaspoon),
                     0..5,
                     6..14,
-                    "1 3⁄4 teaspoon"
+                    "1 3⁄4 teaspoon",
+                    Position::default()
                 )
             ))
         );
@@ -330,7 +540,8 @@ mod test {
                     Measure::single(Rational32::new(13, 4), Unit::Teaspoon),
                     0..4,
                     5..13,
-                    "13⁄4 teaspoon"
+                    "13⁄4 teaspoon",
+                    Position::default()
                 )
             ))
         );
@@ -342,10 +553,122 @@ mod test {
                     Measure::single(Rational32::new(7, 4), Unit::Teaspoon),
                     0..3,
                     4..12,
-                    "1 ¾ teaspoon"
+                    "1 ¾ teaspoon",
+                    Position::default()
                 )
             ))
         );
+        assert_eq!(
+            raw(parse_measure_with(&ParseOptions::fuzzy(1)))("2 cupss other"),
+            Ok((
+                " other",
+                MeasureToken {
+                    fuzzy: Some(FuzzyMatch {
+                        correction: "cups".to_string(),
+                        distance: 1
+                    }),
+                    ..MeasureToken::new(
+                        Measure::single(Rational32::from_integer(2), Unit::Cup),
+                        0..1,
+                        2..7,
+                        "2 cupss",
+                        Position::default()
+                    )
+                }
+            ))
+        );
+        assert_eq!(
+            raw(parse_measure)("2 cupss other").unwrap().1.measure,
+            Measure::single(
+                Rational32::from_integer(2),
+                Unit::unitless("cupss".to_string())
+            )
+        );
+        assert!(raw(parse_measure_with(&ParseOptions::strict()))("3 large").is_err());
+        assert!(raw(parse_measure_with(&ParseOptions::strict()))("3 cups").is_ok());
+        assert!(raw(parse_measure_with(&ParseOptions::count_nouns()))("3 large").is_err());
+        assert!(raw(parse_measure_with(&ParseOptions::count_nouns()))("3 Eggs").is_ok());
+        assert!(raw(parse_measure_with(&ParseOptions::count_nouns()))("3 cups").is_ok());
+        assert_eq!(
+            raw(parse_measure)("3 large eggs, beaten"),
+            Ok((
+                ", beaten",
+                MeasureToken {
+                    modifiers: vec![Modifier::Large],
+                    ..MeasureToken::new(
+                        Measure::single(
+                            Rational32::from_integer(3),
+                            Unit::unitless("eggs".to_string())
+                        ),
+                        0..1,
+                        8..12,
+                        "3 large eggs",
+                        Position::default()
+                    )
+                }
+            ))
+        );
+        assert_eq!(
+            raw(parse_measure)("1 heaping cup flour")
+                .unwrap()
+                .1
+                .modifiers,
+            vec![Modifier::Heaping]
+        );
+        assert_eq!(
+            raw(parse_measure)("2 Extra Large eggs")
+                .unwrap()
+                .1
+                .modifiers,
+            vec![Modifier::ExtraLarge]
+        );
+        assert_eq!(
+            raw(parse_measure)("2 extra-large eggs")
+                .unwrap()
+                .1
+                .modifiers,
+            vec![Modifier::ExtraLarge]
+        );
+        assert_eq!(
+            raw(parse_measure)("1 heaping handful").unwrap().1.measure,
+            Measure::single(
+                Rational32::from_integer(1),
+                Unit::unitless("handful".to_string())
+            )
+        );
+        assert_eq!(
+            raw(parse_measure)("3 large, diced"),
+            Ok((
+                ", diced",
+                MeasureToken::new(
+                    Measure::single(
+                        Rational32::from_integer(3),
+                        Unit::unitless("large".to_string())
+                    ),
+                    0..1,
+                    2..7,
+                    "3 large",
+                    Position::default()
+                )
+            ))
+        );
+        // a modifier doesn't carry a measure onto the next line
+        assert_eq!(
+            raw(parse_measure)("3 large\nflour").unwrap(),
+            (
+                "\nflour",
+                MeasureToken::new(
+                    Measure::single(
+                        Rational32::from_integer(3),
+                        Unit::unitless("large".to_string())
+                    ),
+                    0..1,
+                    2..7,
+                    "3 large",
+                    Position::default()
+                )
+            )
+        );
         // Real Life Tests
         assert!(raw(parse_measure)("3. Line").is_err());
         assert_eq!(
@@ -357,6 +680,48 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            raw(parse_measure)("1/0 cups"),
+            Err(nom::Err::Failure(ParseError::InfiniteNumber {
+                span: Span::new(0..3, 0..3)
+            }))
+        );
+        assert_eq!(
+            raw(parse_measure)("2 1⁄0 cups"),
+            Err(nom::Err::Failure(ParseError::InfiniteNumber {
+                span: Span::new(2..5, 2..7)
+            }))
+        );
+        assert_eq!(
+            raw(parse_measure)("3000000000 cups"),
+            Err(nom::Err::Failure(ParseError::NumberTooLarge {
+                span: Span::new(0..10, 0..10)
+            }))
+        );
+        assert_eq!(
+            raw(parse_measure)("1.00000000001 cups"),
+            Err(nom::Err::Failure(ParseError::NumberTooLarge {
+                span: Span::new(0..13, 0..13)
+            }))
+        );
+        assert_eq!(
+            raw(parse_measure_with(&ParseOptions::strict()))("3 large"),
+            Err(nom::Err::Error(ParseError::UnknownUnit {
+                unit: "large".to_string(),
+                span: Span::new(2..7, 2..7)
+            }))
+        );
+        assert_eq!(
+            ParseError::InfiniteNumber {
+                span: Span::new(12..15, 12..15)
+            }
+            .to_string(),
+            "denominator is zero at 12..15"
+        );
+    }
+
     #[test]
     fn test_parse_integer() {
         assert_eq!(
@@ -427,15 +792,47 @@ mod test {
 
     #[test]
     fn test_parse_units() {
-        assert_eq!(raw(parse_unit)("drop"), Ok(("", Unit::Drop)));
-        assert_eq!(raw(parse_unit)("t"), Ok(("", Unit::Teaspoon)));
-        assert_eq!(raw(parse_unit)("T"), Ok(("", Unit::Tablespoon)));
-        assert_eq!(raw(parse_unit)("Tb"), Ok(("", Unit::Tablespoon)));
-        assert_eq!(raw(parse_unit)("c"), Ok(("", Unit::Cup)));
-        assert_eq!(raw(parse_unit)("C"), Ok(("", Unit::Cup)));
+        assert_eq!(raw(parse_unit)("drop"), Ok(("", vec![Unit::Drop])));
+        assert_eq!(
+            raw(parse_unit)("t"),
+            Ok(("", vec![Unit::Teaspoon, Unit::Tablespoon]))
+        );
+        assert_eq!(
+            raw(parse_unit)("T"),
+            Ok(("", vec![Unit::Tablespoon, Unit::Teaspoon]))
+        );
+        assert_eq!(raw(parse_unit)("Tb"), Ok(("", vec![Unit::Tablespoon])));
+        assert_eq!(
+            raw(parse_unit)("c"),
+            Ok(("", vec![Unit::Cup, Unit::Celsius]))
+        );
+        assert_eq!(
+            raw(parse_unit)("C"),
+            Ok(("", vec![Unit::Cup, Unit::Celsius]))
+        );
+        assert_eq!(raw(parse_unit)("°C"), Ok(("", vec![Unit::Celsius])));
+        assert_eq!(
+            raw(parse_unit)("degrees"),
+            Ok(("", vec![Unit::Fahrenheit, Unit::Celsius]))
+        );
+        assert_eq!(
+            raw(parse_unit)("degrees C other"),
+            Ok((" other", vec![Unit::Celsius]))
+        );
+        assert_eq!(
+            raw(parse_unit)("degrees flour"),
+            Ok((" flour", vec![Unit::Fahrenheit, Unit::Celsius]))
+        );
+        assert_eq!(
+            raw(parse_unit)("large"),
+            Ok(("", vec![Unit::unitless("large".to_string())]))
+        );
         assert_eq!(
             parse_unit(ParserInput::from("C other")),
-            Ok((ParserInput::new(" other", 1), Unit::Cup))
+            Ok((
+                ParserInput::from("C other").advance(1),
+                vec![Unit::Cup, Unit::Celsius]
+            ))
         );
     }
 }
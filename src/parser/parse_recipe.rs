@@ -1,103 +1,80 @@
 use std::fmt::{Debug, Formatter};
 
-use nom::branch::alt;
-use nom::bytes::complete::take;
-use nom::combinator::map;
-use nom::multi::fold_many0;
-use nom::{Finish, InputLength};
-
-use crate::parser::{parse_measure, MeasureToken, ParserInput};
+use crate::parser::disambiguate::disambiguate;
+use crate::parser::parse_yield::find_yield;
+use crate::parser::{measure_tokens_with, MeasureToken, ParseOptions, ParserInput, Yield};
+use crate::ParseError;
 
 pub struct Recipe<'a> {
     pub tokens: Vec<MeasureToken<'a>>,
     pub raw: &'a str,
+    /// Malformed measures we skipped over (`1/0 cups`)
+    pub warnings: Vec<ParseError>,
+    /// How much the recipe makes, if it says (`Serves 4`). Named `yields` since `yield` is taken.
+    pub yields: Option<Yield>,
+}
+
+pub fn parse_recipe<'a, I: Into<ParserInput<'a>>>(input: I) -> Result<Recipe<'a>, ParseError> {
+    parse_recipe_with(input, &ParseOptions::default())
 }
-// pub enum DocumentToken<'a> {
-//     MeasureToken {
-//         measure: Measure,
-//         raw: &'a str,
-//         range: Range<usize>,
-//     },
-//     Other {
-//         raw: &'a str,
-//         range: Range<usize>,
-//     },
-// }
-
-pub fn parse_recipe<'a, I: Into<ParserInput<'a>>>(
+
+pub fn parse_recipe_with<'a, I: Into<ParserInput<'a>>>(
     input: I,
-) -> Result<Recipe<'a>, nom::error::Error<ParserInput<'a>>> {
+    options: &ParseOptions,
+) -> Result<Recipe<'a>, ParseError> {
     let input = input.into();
-    let (remainder, tokens) = fold_many0(
-        // try to parse a measure, if we can't just remove a char off the front and try again
-        alt((map(parse_measure, Some), map(take(1usize), |_| None))),
-        Vec::new,
-        |mut tokens, token| {
-            if let Some(token) = token {
-                tokens.push(token);
-            }
-            tokens
-        },
-    )(input)
-    .finish()?;
+    let (tokens, warnings) = tokenize(input, options)?;
 
-    if remainder.input_len() == 0 {
-        Ok(Recipe {
-            tokens,
-            raw: input.input,
-        })
-    } else {
-        panic!(
-            "We should have consumed the entire document but found: {}",
-            remainder.input
-        )
+    Ok(Recipe::new(
+        input.input,
+        tokens,
+        warnings,
+        find_yield(input),
+    ))
+}
+
+/// Find every measure in `input`, along with warnings for the malformed ones we skipped
+pub(crate) fn tokenize<'a>(
+    input: ParserInput<'a>,
+    options: &ParseOptions,
+) -> Result<(Vec<MeasureToken<'a>>, Vec<ParseError>), ParseError> {
+    let (mut tokens, mut warnings) = (vec![], vec![]);
+    let mut found = measure_tokens_with(input, options);
+    while let Some(next) = found.next_raw() {
+        match next {
+            Ok(token) => tokens.push(token),
+            Err(error @ ParseError::Unconsumed { .. }) => return Err(error),
+            Err(warning) => warnings.push(warning),
+        }
     }
+
+    Ok((tokens, warnings))
 }
 
-// pub fn parse<'a, I: Into<ParserInput<'a>>>(input: I) -> IResult<ParserInput<'a>, Document<'a>> {
-//     let input = input.into();
-//     let (remainder, (mut tokens, other)) = fold_many0(
-//         alt((parse_measure, parse_other)),
-//         || (vec![], None),
-//         |(mut tokens, other): (_, Option<Range<usize>>), token| match (other, token) {
-//             (Some(other_range), DocumentToken::Other { range, .. }) => {
-//                 (tokens, Some(other_range.start..range.end))
-//             }
-//             (None, DocumentToken::Other { range, .. }) => (tokens, Some(range)),
-//             (other, token @ DocumentToken::MeasureToken { .. }) => {
-//                 if let Some(range) = other {
-//                     tokens.push(DocumentToken::Other {
-//                         raw: &input.input[range.clone()],
-//                         range,
-//                     })
-//                 }
-//                 tokens.push(token);
-//                 (tokens, None)
-//             }
-//         },
-//     )(input)?;
-//
-//     if let Some(range) = other {
-//         tokens.push(DocumentToken::Other {
-//             raw: &input.input[range.clone()],
-//             range,
-//         })
-//     }
-//
-//     Ok((remainder, Document { tokens }))
-// }
-
-// fn parse_other(input: ParserInput) -> IResult<ParserInput, DocumentToken> {
-//     let (remainder, token) = alt((multispace1, take_till1(primitive::char::is_whitespace)))(input)?;
-//
-//     Ok((
-//         remainder,
-//         DocumentToken::Other {
-//             raw: token.input,
-//             range: input.range(&token),
-//         },
-//     ))
-// }
+impl<'a> Recipe<'a> {
+    pub(crate) fn new(
+        raw: &'a str,
+        mut tokens: Vec<MeasureToken<'a>>,
+        warnings: Vec<ParseError>,
+        yields: Option<Yield>,
+    ) -> Recipe<'a> {
+        if let Some(found) = &yields {
+            // `Makes 24 cookies` describes the recipe, it isn't something that goes in it
+            tokens.retain(|token| {
+                let range = token.full_range();
+                range.end <= found.range.start || range.start >= found.range.end
+            });
+        }
+        disambiguate(raw.into(), &mut tokens);
+
+        Recipe {
+            tokens,
+            raw,
+            warnings,
+            yields,
+        }
+    }
+}
 
 impl<'a> Debug for Recipe<'a> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -105,31 +82,134 @@ impl<'a> Debug for Recipe<'a> {
     }
 }
 
-// impl<'a> Debug for DocumentToken<'a> {
-//     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-//         match self {
-//             DocumentToken::MeasureToken {
-//                 measure,
-//                 raw,
-//                 range,
-//             } => {
-//                 write!(f, "({range:?}: {measure:?} @ \"{raw}\")")
-//             }
-//             DocumentToken::Other { raw, range } => write!(f, "({range:?}: \"{raw}\")"),
-//         }
-//     }
-// }
-
 #[cfg(test)]
 mod test {
+    use crate::parser::{Position, Span, UnitMode};
+    use crate::Unit;
+
+    use super::*;
+
+    #[test]
+    fn test_unit_modes() {
+        let recipe = "Whisk 3 large eggs 10 times with 2 cups milk, then add 2 cloves garlic";
+        let units = |options: &ParseOptions| {
+            parse_recipe_with(recipe, options)
+                .unwrap()
+                .tokens
+                .into_iter()
+                .map(|token| token.measure.main_unit().clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            units(&ParseOptions::default()),
+            vec![
+                Unit::unitless("eggs".to_string()),
+                Unit::unitless("times".to_string()),
+                Unit::Cup,
+                Unit::unitless("cloves".to_string())
+            ]
+        );
+        assert_eq!(
+            units(&ParseOptions::count_nouns()),
+            vec![
+                Unit::unitless("eggs".to_string()),
+                Unit::Cup,
+                Unit::unitless("cloves".to_string())
+            ]
+        );
+        assert_eq!(
+            units(&ParseOptions {
+                unit_mode: UnitMode::CountNouns(vec!["times".to_string()]),
+                ..Default::default()
+            }),
+            vec![Unit::unitless("times".to_string()), Unit::Cup]
+        );
+        assert_eq!(units(&ParseOptions::strict()), vec![Unit::Cup]);
+    }
+
+    #[test]
+    fn test_warnings() {
+        let recipe =
+            parse_recipe("Add 1/0 cups of flour and 3000000000 eggs then 2 cups milk").unwrap();
+        assert_eq!(recipe.tokens.len(), 1);
+        assert_eq!(recipe.tokens[0].measure.main_unit(), &Unit::Cup);
+        assert_eq!(
+            recipe
+                .warnings
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "denominator is zero at 4..7",
+                "number is too large at 26..36"
+            ]
+        );
+
+        let recipe = parse_recipe("½ cup 1⁄0 tsp").unwrap();
+        assert_eq!(
+            recipe.warnings,
+            vec![ParseError::InfiniteNumber {
+                span: Span::new(6..9, 7..12)
+            }]
+        );
+    }
 
     #[test]
     fn test_parse() {
-        // println!("Document: {:?}", parse_recipe("(2 large lemons)"));
-        // println!("Document: {:?}", parse_recipe("(2 1/3 cups)"));
-        // assert_eq!(
-        //     parse_recipe("½2 cup cooked red lentils").unwrap().tokens[0].range,
-        //     1..6
-        // );
+        let units = |raw| {
+            parse_recipe(raw)
+                .unwrap()
+                .tokens
+                .into_iter()
+                .map(|token| (token.raw.to_string(), token.full_range()))
+                .collect::<Vec<_>>()
+        };
+
+        // step numbers aren't measures, or part of the measure after them
+        assert_eq!(units("3. Line a pan"), vec![]);
+        assert_eq!(
+            units("1. Beat 2 eggs\n2. 3 eggs more\n10) Stir"),
+            vec![
+                ("2 eggs".to_string(), 8..14),
+                ("3 eggs".to_string(), 18..24)
+            ]
+        );
+        assert_eq!(
+            units("Add 2. 5 cups"),
+            vec![("2. 5 cups".to_string(), 4..13)]
+        );
+    }
+
+    #[test]
+    fn test_positions() {
+        // `🥚` is 4 bytes and 2 UTF-16 code units, `½` is 2 bytes and 1 code unit
+        let recipe = parse_recipe("🥚 Eggs\nWhisk ½ cup milk").unwrap();
+        let token = &recipe.tokens[0];
+
+        assert_eq!(token.full_range(), 13..18);
+        assert_eq!(token.byte_range(), 16..22);
+        assert_eq!(token.utf16_range(), 14..19);
+        assert_eq!(
+            token.positions(),
+            Position {
+                byte: 16,
+                char: 13,
+                utf16: 14,
+                line: 1,
+                column: 6,
+                utf16_column: 6,
+            }..Position {
+                byte: 22,
+                char: 18,
+                utf16: 19,
+                line: 1,
+                column: 11,
+                utf16_column: 11,
+            }
+        );
+        assert_eq!(token.number_positions().end.column, 7);
+        assert_eq!(token.unit_positions().start.column, 8);
+        assert_eq!(&recipe.raw[token.byte_range()], "½ cup");
     }
 }
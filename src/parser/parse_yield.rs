@@ -0,0 +1,129 @@
+use std::ops::Range;
+
+use nom::branch::alt;
+use nom::bytes::complete::tag_no_case;
+use nom::character::complete::{alpha1, char, satisfy, space0, space1};
+use nom::combinator::{consumed, map, not, opt};
+use nom::sequence::{preceded, terminated, tuple};
+use nom::IResult;
+use num_rational::Rational32;
+
+use crate::parser::{parse_number, ParserInput};
+use crate::ParseError;
+
+/// How much a recipe makes (`Serves 4`, `Makes 24 cookies`, `Yield: 2 loaves`)
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Yield {
+    pub amount: Rational32,
+    /// What the recipe makes, [`None`] when it's just a number of servings
+    pub noun: Option<String>,
+    /// The chars of the whole statement in the recipe
+    pub range: Range<usize>,
+    /// The chars of the amount in the recipe
+    pub number_range: Range<usize>,
+}
+
+/// Find the first line that says how much the recipe makes
+pub(crate) fn find_yield(input: ParserInput) -> Option<Yield> {
+    let mut position = input.position;
+    for line in input.input.split_inclusive('\n') {
+        let line_input = ParserInput::new(line, position);
+        if let Ok((_, found)) = parse_yield(line_input) {
+            return Some(found);
+        }
+        position = position.advance(line);
+    }
+
+    None
+}
+
+/// Parse a yield statement at the start of a line
+pub(crate) fn parse_yield(input: ParserInput) -> IResult<ParserInput, Yield, ParseError> {
+    let (remainder, (statement, ((number_raw, amount), noun))) = preceded(
+        space0,
+        consumed(preceded(
+            tuple((
+                yield_word,
+                opt(char(':')),
+                space0,
+                opt(terminated(
+                    alt((
+                        tag_no_case("about"),
+                        tag_no_case("approximately"),
+                        tag_no_case("around"),
+                    )),
+                    space1,
+                )),
+            )),
+            tuple((
+                consumed(parse_number),
+                opt(preceded(
+                    space1,
+                    map(alpha1, |noun: ParserInput| noun.input),
+                )),
+            )),
+        )),
+    )(input)?;
+
+    Ok((
+        remainder,
+        Yield {
+            amount,
+            noun: noun.map(ToString::to_string),
+            range: statement.range(),
+            number_range: number_raw.range(),
+        },
+    ))
+}
+
+fn yield_word(input: ParserInput) -> IResult<ParserInput, ParserInput, ParseError> {
+    terminated(
+        alt((
+            tag_no_case("servings"),
+            tag_no_case("serving"),
+            tag_no_case("serves"),
+            tag_no_case("yields"),
+            tag_no_case("yield"),
+            tag_no_case("makes"),
+        )),
+        not(satisfy(char::is_alphanumeric)),
+    )(input)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::parse_recipe;
+
+    use super::*;
+
+    fn yields(raw: &str) -> Option<(Rational32, Option<String>)> {
+        parse_recipe(raw)
+            .unwrap()
+            .yields
+            .map(|found| (found.amount, found.noun))
+    }
+
+    #[test]
+    fn test_yield() {
+        let amount = Rational32::from_integer;
+        assert_eq!(yields("Serves 4"), Some((amount(4), None)));
+        assert_eq!(yields("Servings: 6\n2 cups flour"), Some((amount(6), None)));
+        assert_eq!(
+            yields("Pancakes\nMakes about 24 cookies."),
+            Some((amount(24), Some("cookies".to_string())))
+        );
+        assert_eq!(
+            yields("YIELD: 1 ½ loaves"),
+            Some((Rational32::new(3, 2), Some("loaves".to_string())))
+        );
+        assert_eq!(yields("Mix until it makes a paste"), None);
+        assert_eq!(yields("The servings are generous"), None);
+
+        let recipe = parse_recipe("Yield: 2 loaves\n3 cups flour").unwrap();
+        let found = recipe.yields.unwrap();
+        assert_eq!(found.range, 0..15);
+        assert_eq!(found.number_range, 7..8);
+        // the yield isn't an ingredient
+        assert_eq!(recipe.tokens.len(), 1);
+    }
+}
@@ -0,0 +1,48 @@
+use crate::parser::{DocumentToken, MeasureToken, Recipe};
+
+impl<'a> Recipe<'a> {
+    /// Rebuild the recipe text, replacing every measure with whatever `render` returns for it and
+    /// leaving the text between measures untouched
+    pub fn render_with<F, S>(&self, mut render: F) -> String
+    where
+        F: FnMut(&MeasureToken<'a>) -> S,
+        S: AsRef<str>,
+    {
+        let mut rendered = String::with_capacity(self.raw.len());
+        for token in self.iter() {
+            match token {
+                DocumentToken::Measure(token) => rendered.push_str(render(token).as_ref()),
+                DocumentToken::Other { raw, .. } => rendered.push_str(raw),
+            }
+        }
+
+        rendered
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::parse_recipe;
+
+    #[test]
+    fn test_render_with() {
+        let recipe = parse_recipe("Whisk ½ cup milk with 2 eggs until smooth").unwrap();
+
+        assert_eq!(
+            recipe.render_with(|token| format!("[{}]", token.raw)),
+            "Whisk [½ cup] milk with [2 eggs] until smooth"
+        );
+        assert_eq!(
+            recipe.render_with(|token| token.number_text()),
+            "Whisk ½ milk with 2 until smooth"
+        );
+        assert_eq!(
+            recipe.render_with(|token| token.text()),
+            "Whisk ½ cup milk with 2 eggs until smooth"
+        );
+        assert_eq!(
+            parse_recipe("½½ tsp½ tsp").unwrap().render_with(|_| "x"),
+            "½xx"
+        );
+    }
+}
@@ -0,0 +1,361 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use thiserror::Error;
+
+use crate::parser::parse_yield::find_yield;
+use crate::parser::unit_index::lookup_unit;
+use crate::parser::{
+    starts_number, tokenize, Confidence, MeasureToken, ParseOptions, ParserInput, Position, Recipe,
+};
+use crate::{Measure, ParseError};
+
+/// A change to a recipe's text, replacing the bytes in `range` with `replacement`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    pub fn new<S: Into<String>>(range: Range<usize>, replacement: S) -> TextEdit {
+        TextEdit {
+            range,
+            replacement: replacement.into(),
+        }
+    }
+
+    /// The text after making this edit to `text`
+    pub fn apply(&self, text: &str) -> String {
+        let mut edited =
+            String::with_capacity(text.len() - self.range.len() + self.replacement.len());
+        edited.push_str(&text[..self.range.start]);
+        edited.push_str(&self.replacement);
+        edited.push_str(&text[self.range.end..]);
+
+        edited
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ReparseError {
+    #[error("edit {range:?} isn't within the {len} bytes of the previous text")]
+    EditOutOfBounds { range: Range<usize>, len: usize },
+    #[error("the edited text doesn't match the edit")]
+    EditMismatch,
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+}
+
+pub fn reparse_recipe<'a>(
+    previous: &Recipe,
+    edit: &TextEdit,
+    edited: &'a str,
+) -> Result<Recipe<'a>, ReparseError> {
+    reparse_recipe_with(previous, edit, edited, &ParseOptions::default())
+}
+
+/// Update `previous` after `edit` turned its text into `edited`, only parsing the lines around the
+/// edit again. This gives the same recipe as parsing `edited` from scratch, as long as `options`
+/// are the ones `previous` was parsed with.
+pub fn reparse_recipe_with<'a>(
+    previous: &Recipe,
+    edit: &TextEdit,
+    edited: &'a str,
+    options: &ParseOptions,
+) -> Result<Recipe<'a>, ReparseError> {
+    let old = previous.raw;
+    if old.get(edit.range.clone()).is_none() {
+        return Err(ReparseError::EditOutOfBounds {
+            range: edit.range.clone(),
+            len: old.len(),
+        });
+    }
+    let replaced = edit.range.start..edit.range.start + edit.replacement.len();
+    if edited.len() != old.len() - edit.range.len() + edit.replacement.len()
+        || edited.get(replaced) != Some(edit.replacement.as_str())
+        || !edited.is_char_boundary(edit.range.start)
+    {
+        return Err(ReparseError::EditMismatch);
+    }
+
+    let mut old_tokens = previous
+        .tokens
+        .iter()
+        .cloned()
+        .map(undecided)
+        .collect::<Vec<_>>();
+
+    // the tokens in the yield were left out of the recipe, so find them again
+    if let Some(found) = &previous.yields {
+        let bytes = byte_for_char(old, &old_tokens, found.range.start)
+            ..byte_for_char(old, &old_tokens, found.range.end);
+        let window = window(old, bytes, |_| true);
+        let (rescanned, _) = tokenize(input_at(old, &old_tokens, window.clone()), options)?;
+        let first = old_tokens.partition_point(|token| token.start.byte < window.start);
+        let last = old_tokens.partition_point(|token| token.start.byte < window.end);
+        old_tokens.splice(first..last, rescanned);
+    }
+
+    let new_byte = |old_byte: usize| old_byte + edit.replacement.len() - edit.range.len();
+    let window = window(old, edit.range.clone(), |p| safe_break(edited, new_byte(p)));
+    let new_window = window.start..new_byte(window.end);
+    let input = input_at(edited, &old_tokens, new_window.clone());
+    let (window_tokens, window_warnings) = tokenize(input, options)?;
+
+    // everything after the window is on a later line, so only its columns stay the same
+    let from = input.position.advance(&old[window.clone()]);
+    let to = input.position.advance(input.input);
+    let moved = |position: Position| Position {
+        byte: position.byte - from.byte + to.byte,
+        char: position.char - from.char + to.char,
+        utf16: position.utf16 - from.utf16 + to.utf16,
+        line: position.line - from.line + to.line,
+        ..position
+    };
+    let moved_chars =
+        |range: &Range<usize>| range.start - from.char + to.char..range.end - from.char + to.char;
+
+    let mut tokens = vec![];
+    let mut after = vec![];
+    for token in old_tokens {
+        if token.start.byte < window.start {
+            tokens.push(rebind(
+                token,
+                edited,
+                |position| position,
+                |range| range.clone(),
+            ));
+        } else if token.start.byte >= window.end {
+            after.push(rebind(token, edited, moved, moved_chars));
+        }
+    }
+    tokens.extend(window_tokens);
+    tokens.append(&mut after);
+
+    let mut warnings = vec![];
+    let mut warnings_after = vec![];
+    for warning in &previous.warnings {
+        let bytes = &warning.span().bytes;
+        if bytes.end <= window.start {
+            warnings.push(warning.clone());
+        } else if bytes.start >= window.end {
+            let mut warning = warning.clone();
+            let span = warning.span_mut();
+            span.chars = moved_chars(&span.chars);
+            span.bytes = new_byte(span.bytes.start)..new_byte(span.bytes.end);
+            warnings_after.push(warning);
+        }
+    }
+    warnings.extend(window_warnings);
+    warnings.append(&mut warnings_after);
+
+    Ok(Recipe::new(
+        edited,
+        tokens,
+        warnings,
+        find_yield(edited.into()),
+    ))
+}
+
+/// Grow `range` out to line breaks we can safely start parsing from again. The end has to be safe
+/// in the edited text as well, which `also_safe` checks.
+fn window(text: &str, range: Range<usize>, also_safe: impl Fn(usize) -> bool) -> Range<usize> {
+    let start = text[..range.start]
+        .rmatch_indices('\n')
+        .map(|(i, _)| i)
+        .find(|&i| safe_break(text, i))
+        .unwrap_or(0);
+    let end = text[range.end..]
+        .match_indices('\n')
+        .map(|(i, _)| range.end + i)
+        .find(|&i| safe_break(text, i) && also_safe(i))
+        .unwrap_or(text.len());
+
+    start..end
+}
+
+/// Whether the line break at `p` can't be part of a measure, which means the tokens after it don't
+/// depend on anything before it. Measures only carry on over a line break after a number, a
+/// fraction's `/` or a decimal's `.`.
+fn safe_break(text: &str, p: usize) -> bool {
+    let before = text[..p].trim_end();
+    let mut chars = before.chars().rev();
+    match chars.next() {
+        None => true,
+        Some(c) if starts_number(c) => false,
+        Some('.' | '/' | '⁄') => !chars
+            .find(|c| !c.is_whitespace())
+            .is_some_and(|c| c.is_ascii_digit()),
+        Some(_) => true,
+    }
+}
+
+/// Put back the unit order a token had before we picked between its ambiguous units, since
+/// picking again depends on it
+fn undecided(mut token: MeasureToken) -> MeasureToken {
+    if token.alternatives.is_empty() {
+        return token;
+    }
+
+    let mut units = lookup_unit(&token.unit_text()).unwrap_or_default().to_vec();
+    if let (Measure::Single(single), false) = (&mut token.measure, units.is_empty()) {
+        single.unit = units.remove(0);
+        token.alternatives = units;
+        token.confidence = Confidence::Guess;
+    }
+
+    token
+}
+
+/// Move a token over to the edited text
+fn rebind<'a>(
+    token: MeasureToken,
+    edited: &'a str,
+    moved: impl Fn(Position) -> Position,
+    moved_chars: impl Fn(&Range<usize>) -> Range<usize>,
+) -> MeasureToken<'a> {
+    let start = moved(token.start);
+    MeasureToken {
+        number_range: moved_chars(&token.number_range),
+        unit_range: moved_chars(&token.unit_range),
+        raw: Cow::Borrowed(&edited[start.byte..start.byte + token.raw.len()]),
+        start,
+        ..token
+    }
+}
+
+/// The input for `range` of `text`, placed using the closest token before it
+fn input_at<'a>(text: &'a str, tokens: &[MeasureToken], range: Range<usize>) -> ParserInput<'a> {
+    let before = tokens.partition_point(|token| token.start.byte <= range.start);
+    let anchor = before
+        .checked_sub(1)
+        .map(|i| tokens[i].start)
+        .unwrap_or_default();
+
+    ParserInput::new(
+        &text[range.clone()],
+        anchor.advance(&text[anchor.byte..range.start]),
+    )
+}
+
+fn byte_for_char(text: &str, tokens: &[MeasureToken], char_index: usize) -> usize {
+    let before = tokens.partition_point(|token| token.start.char <= char_index);
+    let anchor = before
+        .checked_sub(1)
+        .map(|i| tokens[i].start)
+        .unwrap_or_default();
+
+    text[anchor.byte..]
+        .char_indices()
+        .nth(char_index - anchor.char)
+        .map_or(text.len(), |(i, _)| anchor.byte + i)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::parse_recipe;
+
+    use super::*;
+
+    const RECIPE: &str = "Pancakes
+Serves 4
+
+1 ½ cups flour, 2 tbsp sugar
+3 large
+eggs and 1 cup milk
+
+1. Heat the pan to 350 degrees.
+2. Add 1/0 cups of batter for 2
+minutes a side.
+";
+
+    #[test]
+    fn test_reparse_recipe() {
+        let previous = parse_recipe(RECIPE).unwrap();
+        let edit = TextEdit::new(38..38, "½ ");
+        let edited = edit.apply(RECIPE);
+        let recipe = reparse_recipe(&previous, &edit, &edited).unwrap();
+
+        assert_eq!(recipe.tokens[1].raw, "2 ½ tbsp");
+        assert_eq!(recipe.tokens, parse_recipe(edited.as_str()).unwrap().tokens);
+
+        let beyond = TextEdit::new(RECIPE.len()..RECIPE.len() + 1, "");
+        assert!(matches!(
+            reparse_recipe(&previous, &beyond, RECIPE),
+            Err(ReparseError::EditOutOfBounds { .. })
+        ));
+        assert!(matches!(
+            reparse_recipe(
+                &previous,
+                &TextEdit::new(Range { start: 5, end: 2 }, ""),
+                RECIPE
+            ),
+            Err(ReparseError::EditOutOfBounds { .. })
+        ));
+        assert!(matches!(
+            reparse_recipe(&previous, &edit, RECIPE),
+            Err(ReparseError::EditMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_reparse_matches_parse() {
+        let pieces = [
+            "2",
+            "½",
+            " cups",
+            "\n",
+            ".",
+            "/",
+            "0",
+            " large",
+            " eggs",
+            "Serves 4\n",
+            "Makes 6 buns",
+            " C",
+            "2. ",
+            "degrees",
+            ", ",
+            "🥚",
+            "",
+        ];
+        // a little linear congruential generator, so the edits are the same every run
+        let mut seed = 7u64;
+        let mut random = |below: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % below
+        };
+
+        let mut text = RECIPE.to_string();
+        let mut previous_text = text.clone();
+        for _ in 0..400 {
+            let boundaries = (0..=text.len())
+                .filter(|&i| text.is_char_boundary(i))
+                .collect::<Vec<_>>();
+            let start = boundaries[random(boundaries.len())];
+            let end = boundaries
+                .iter()
+                .copied()
+                .filter(|&i| i >= start)
+                .nth(random(3))
+                .unwrap_or(start);
+            let edit = TextEdit::new(start..end, pieces[random(pieces.len())]);
+
+            let previous = parse_recipe(previous_text.as_str()).unwrap();
+            text = edit.apply(&previous_text);
+            let reparsed = reparse_recipe(&previous, &edit, &text).unwrap();
+            let parsed = parse_recipe(text.as_str()).unwrap();
+
+            assert_eq!(reparsed.tokens, parsed.tokens, "after {edit:?} in {text:?}");
+            assert_eq!(
+                reparsed.warnings, parsed.warnings,
+                "after {edit:?} in {text:?}"
+            );
+            assert_eq!(reparsed.yields, parsed.yields);
+            previous_text = text.clone();
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+/// A location in the original text, as both char and byte offsets
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, Default)]
+pub struct Span {
+    pub chars: Range<usize>,
+    pub bytes: Range<usize>,
+}
+
+impl Span {
+    pub fn new(chars: Range<usize>, bytes: Range<usize>) -> Span {
+        Span { chars, bytes }
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.chars.start, self.chars.end)
+    }
+}
+
+/// A place in the original text, counted in each of the ways editors and other languages count
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Copy, Clone, Default)]
+pub struct Position {
+    pub byte: usize,
+    pub char: usize,
+    /// UTF-16 code units, the way JavaScript and the language server protocol count
+    pub utf16: usize,
+    /// The line, starting from 0
+    pub line: usize,
+    /// Chars since the start of the line
+    pub column: usize,
+    /// UTF-16 code units since the start of the line
+    pub utf16_column: usize,
+}
+
+impl Position {
+    /// The position just past `text`, when `text` starts here
+    pub fn advance(self, text: &str) -> Position {
+        let mut position = self;
+        for c in text.chars() {
+            position.byte += c.len_utf8();
+            position.char += 1;
+            position.utf16 += c.len_utf16();
+            if c == '\n' {
+                position.line += 1;
+                position.column = 0;
+                position.utf16_column = 0;
+            } else {
+                position.column += 1;
+                position.utf16_column += c.len_utf16();
+            }
+        }
+
+        position
+    }
+}
+
+/// Finds the [`Position`] of byte offsets in a text, walking forward from the last one it found
+pub(crate) struct PositionCursor<'a> {
+    text: &'a str,
+    position: Position,
+}
+
+impl<'a> PositionCursor<'a> {
+    pub(crate) fn new(text: &'a str) -> Self {
+        PositionCursor {
+            text,
+            position: Position::default(),
+        }
+    }
+
+    pub(crate) fn at(&mut self, byte: usize) -> Position {
+        if byte < self.position.byte {
+            self.position = Position::default();
+        }
+        self.position = self.position.advance(&self.text[self.position.byte..byte]);
+
+        self.position
+    }
+}
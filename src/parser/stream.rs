@@ -0,0 +1,255 @@
+use std::collections::VecDeque;
+use std::io::BufRead;
+
+use nom::branch::alt;
+use nom::bytes::complete::{take, take_till1};
+use nom::character::complete::char;
+use nom::combinator::{map, opt};
+use nom::sequence::pair;
+use nom::{IResult, InputLength, Parser, Slice};
+use thiserror::Error;
+
+use crate::parser::disambiguate::disambiguate;
+use crate::parser::structure::step_marker;
+use crate::parser::{
+    parse_measure_with, starts_number, MeasureToken, ParseOptions, ParserInput, Position, UnitMode,
+};
+use crate::ParseError;
+
+/// Lazily finds the measures in some text, yielding each one as soon as it's found along with
+/// warnings for the malformed ones (`1/0 cups`).
+///
+/// Since we can't see the whole document, ambiguous units (`C`, `degrees`) are picked using only
+/// the line they're on, and measures that describe the recipe (`Makes 24 cookies`) aren't left out
+/// like they are by [`crate::parser::parse_recipe`].
+pub struct MeasureTokens<'a, 'o> {
+    raw: ParserInput<'a>,
+    remainder: ParserInput<'a>,
+    options: &'o ParseOptions,
+    at_start: bool,
+}
+
+pub fn measure_tokens<'a, I: Into<ParserInput<'a>>>(input: I) -> MeasureTokens<'a, 'static> {
+    const DEFAULT: &ParseOptions = &ParseOptions {
+        fuzzy_threshold: None,
+        unit_mode: UnitMode::Lenient,
+    };
+
+    measure_tokens_with(input, DEFAULT)
+}
+
+pub fn measure_tokens_with<'a, 'o, I: Into<ParserInput<'a>>>(
+    input: I,
+    options: &'o ParseOptions,
+) -> MeasureTokens<'a, 'o> {
+    let input = input.into();
+    MeasureTokens {
+        raw: input,
+        remainder: input,
+        options,
+        at_start: true,
+    }
+}
+
+impl<'a> MeasureTokens<'a, '_> {
+    /// Find the next measure or warning without picking between ambiguous units
+    pub(crate) fn next_raw(&mut self) -> Option<Result<MeasureToken<'a>, ParseError>> {
+        if std::mem::take(&mut self.at_start) {
+            if let Ok((remainder, _)) = step_marker(self.remainder) {
+                self.remainder = remainder;
+            }
+        }
+
+        while self.remainder.input_len() > 0 {
+            match step(self.remainder, self.options) {
+                Ok((remainder, found)) => {
+                    self.remainder = remainder;
+                    if found.is_some() {
+                        return found;
+                    }
+                }
+                // `step` can always take a char and `recover` turns failures into errors, so this
+                // is a bug in the parser
+                Err(_) => {
+                    let span = self.remainder.span();
+                    self.remainder = self.remainder.advance(self.remainder.input_len());
+                    return Some(Err(ParseError::Unconsumed { span }));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> Iterator for MeasureTokens<'a, '_> {
+    type Item = Result<MeasureToken<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut found = self.next_raw()?;
+        if let Ok(token) = &mut found {
+            disambiguate(self.raw, std::slice::from_mut(token));
+        }
+
+        Some(found)
+    }
+}
+
+/// Try to parse a measure, if we can't skip ahead to the next thing that could start one. Step
+/// numbers (`2. 3 eggs`) get skipped along with the line break before them.
+fn step<'a>(
+    input: ParserInput<'a>,
+    options: &ParseOptions,
+) -> IResult<ParserInput<'a>, Option<Result<MeasureToken<'a>, ParseError>>, ParseError> {
+    alt((
+        map(recover(parse_measure_with(options)), Some),
+        map(pair(char('\n'), opt(step_marker)), |_| None),
+        map(take_till1(|c| c == '\n' || starts_number(c)), |_| None),
+        map(take(1usize), |_| None),
+    ))(input)
+}
+
+/// Turn a [`nom::Err::Failure`] into an error value and skip past the text that caused it, so one
+/// malformed measure doesn't stop us from parsing the rest of the recipe
+fn recover<'a, O, P>(
+    mut parser: P,
+) -> impl FnMut(ParserInput<'a>) -> IResult<ParserInput<'a>, Result<O, ParseError>, ParseError>
+where
+    P: Parser<ParserInput<'a>, O, ParseError>,
+{
+    move |input: ParserInput<'a>| match parser.parse(input) {
+        Ok((remainder, output)) => Ok((remainder, Ok(output))),
+        Err(nom::Err::Failure(error)) => {
+            let skip = error.span().bytes.end.saturating_sub(input.position.byte);
+            if skip > 0 && skip <= input.input_len() {
+                Ok((input.slice(skip..), Err(error)))
+            } else {
+                Err(nom::Err::Error(error))
+            }
+        }
+        Err(error) => Err(error),
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ReadError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+}
+
+/// Finds the measures in a reader a line at a time, so huge recipe dumps can be scanned without
+/// holding them in memory. Positions are still counted from the start of the reader.
+///
+/// Like [`MeasureTokens`] ambiguous units are picked using only their line, and a measure split
+/// across two lines (`2\ncups`) isn't found.
+pub struct ReadMeasureTokens<R> {
+    reader: R,
+    options: ParseOptions,
+    line: String,
+    position: Position,
+    pending: VecDeque<Result<MeasureToken<'static>, ParseError>>,
+    done: bool,
+}
+
+pub fn read_measure_tokens<R: BufRead>(reader: R) -> ReadMeasureTokens<R> {
+    read_measure_tokens_with(reader, ParseOptions::default())
+}
+
+pub fn read_measure_tokens_with<R: BufRead>(
+    reader: R,
+    options: ParseOptions,
+) -> ReadMeasureTokens<R> {
+    ReadMeasureTokens {
+        reader,
+        options,
+        line: String::new(),
+        position: Position::default(),
+        pending: VecDeque::new(),
+        done: false,
+    }
+}
+
+impl<R: BufRead> Iterator for ReadMeasureTokens<R> {
+    type Item = Result<MeasureToken<'static>, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(found) = self.pending.pop_front() {
+                return Some(found.map_err(ReadError::from));
+            }
+            if self.done {
+                return None;
+            }
+
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => self.done = true,
+                Ok(_) => {
+                    let line = ParserInput::new(&self.line, self.position);
+                    self.pending.extend(
+                        measure_tokens_with(line, &self.options)
+                            .map(|found| found.map(MeasureToken::into_owned)),
+                    );
+                    self.position = self.position.advance(&self.line);
+                }
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(error.into()));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::parser::parse_recipe;
+    use crate::Unit;
+
+    use super::*;
+
+    const RECIPE: &str =
+        "1. Preheat the oven to 350 degrees\n2. Add 1/0 cups of flour, then 2 C milk\n\
+        🥚 Whisk ½ cup cream with 3 eggs";
+
+    #[test]
+    fn test_measure_tokens() {
+        let mut tokens = measure_tokens(RECIPE);
+        let oven = tokens.next().unwrap().unwrap();
+        assert_eq!(oven.measure.main_unit(), &Unit::Fahrenheit);
+        assert!(matches!(
+            tokens.next(),
+            Some(Err(ParseError::InfiniteNumber { .. }))
+        ));
+
+        let rest = tokens.collect::<Result<Vec<_>, _>>().unwrap();
+        let recipe = parse_recipe(RECIPE).unwrap();
+        assert_eq!(rest, recipe.tokens[1..]);
+
+        // bad measures are reported and skipped without losing the text after them
+        let found = measure_tokens("1/0 cups\n3000000000 eggs\n2. 99999999999 g\n2 cups milk")
+            .collect::<Vec<_>>();
+        assert_eq!(found.len(), 4);
+        assert!(found[..3].iter().all(Result::is_err));
+        assert_eq!(found[3].as_ref().unwrap().raw, "2 cups");
+    }
+
+    #[test]
+    fn test_read_measure_tokens() {
+        let read = read_measure_tokens(Cursor::new(RECIPE))
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>();
+        let recipe = parse_recipe(RECIPE).unwrap();
+        assert_eq!(read, recipe.tokens);
+        assert_eq!(read[3].start.line, 2);
+        assert_eq!(read[3].byte_range(), 104..110);
+
+        let invalid =
+            read_measure_tokens(Cursor::new(b"2 cups\n\xff".as_slice())).collect::<Vec<_>>();
+        assert!(matches!(invalid[..], [Ok(_), Err(ReadError::Io(_))]));
+    }
+}
@@ -0,0 +1,385 @@
+use std::ops::Range;
+
+use nom::branch::alt;
+use nom::bytes::complete::tag_no_case;
+use nom::character::complete::{digit1, one_of, space0, space1};
+use nom::combinator::{map_opt, opt};
+use nom::sequence::{delimited, preceded, terminated, tuple};
+use nom::IResult;
+
+use crate::parser::{MeasureToken, ParserInput, Position, Recipe, Span};
+use crate::ParseError;
+
+/// Headings that start the ingredients list
+const INGREDIENT_HEADINGS: [&str; 2] = ["ingredients", "you will need"];
+/// Headings that start the instructions
+const STEP_HEADINGS: [&str; 6] = [
+    "instructions",
+    "directions",
+    "method",
+    "steps",
+    "preparation",
+    "to make",
+];
+
+/// A recipe split into its title, ingredients and steps
+#[derive(Debug, Eq, PartialEq)]
+pub struct Structure<'r, 'a> {
+    pub title: Option<Block<'r, 'a>>,
+    pub ingredient_groups: Vec<IngredientGroup<'r, 'a>>,
+    pub steps: Vec<Step<'r, 'a>>,
+}
+
+/// Some text from the recipe along with the measures in it
+#[derive(Debug, Eq, PartialEq)]
+pub struct Block<'r, 'a> {
+    pub text: &'a str,
+    /// The chars this text covers in the recipe
+    pub range: Range<usize>,
+    pub tokens: &'r [MeasureToken<'a>],
+}
+
+/// Ingredients listed together, usually under a heading like `For the sauce:`
+#[derive(Debug, Eq, PartialEq)]
+pub struct IngredientGroup<'r, 'a> {
+    pub heading: Option<&'a str>,
+    /// One block for each line in the group
+    pub lines: Vec<Block<'r, 'a>>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Step<'r, 'a> {
+    /// The number the recipe gave the step, [`None`] for bulleted or plain paragraphs
+    pub number: Option<u32>,
+    /// The step without its number or bullet
+    pub body: Block<'r, 'a>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Section {
+    Unknown,
+    /// Ingredients, and whether a heading told us so
+    Ingredients(bool),
+    Steps,
+}
+
+/// Parse a step number at the start of a line (`1.`, `2)`, `Step 3:`)
+pub(crate) fn step_marker(input: ParserInput) -> IResult<ParserInput, u32, ParseError> {
+    delimited(
+        space0,
+        alt((
+            terminated(step_number, one_of(".)")),
+            preceded(
+                tuple((tag_no_case("step"), space1)),
+                terminated(step_number, opt(one_of(":."))),
+            ),
+        )),
+        space1,
+    )(input)
+}
+
+/// A step's number. Digits too big to be one just aren't a step marker, they're left for the
+/// measure parser to complain about.
+fn step_number(input: ParserInput) -> IResult<ParserInput, u32, ParseError> {
+    map_opt(digit1, |digits: ParserInput| digits.input.parse().ok())(input)
+}
+
+impl<'a> Recipe<'a> {
+    /// Split the recipe into a title, groups of ingredients and steps, based on its headings,
+    /// numbering and bullets
+    pub fn structure(&self) -> Structure<'_, 'a> {
+        let mut builder = Builder::new(self);
+
+        let mut position = Position::default();
+        let mut previous_blank = true;
+        for line in self.raw.split_inclusive('\n') {
+            let content = line.trim_end_matches(['\n', '\r']);
+            let input = ParserInput::new(content, position);
+            position = position.advance(line);
+
+            let Some(trimmed) = trim(input) else {
+                previous_blank = true;
+                continue;
+            };
+            builder.line(trimmed, previous_blank);
+            previous_blank = false;
+        }
+
+        builder.build()
+    }
+}
+
+pub(crate) struct Builder<'r, 'a> {
+    recipe: &'r Recipe<'a>,
+    section: Section,
+    title: Option<Span>,
+    groups: Vec<(Option<Span>, Vec<Span>)>,
+    steps: Vec<(Option<u32>, Span)>,
+}
+
+impl<'r, 'a> Builder<'r, 'a> {
+    pub(crate) fn new(recipe: &'r Recipe<'a>) -> Self {
+        Builder {
+            recipe,
+            section: Section::Unknown,
+            title: None,
+            groups: vec![],
+            steps: vec![],
+        }
+    }
+
+    /// Work out what a trimmed line of plain text is from its markup
+    pub(crate) fn line(&mut self, line: ParserInput<'a>, previous_blank: bool) {
+        if let Some(heading) = heading(line) {
+            self.heading(heading, !line.input.ends_with(':'));
+        } else if let Ok((body, number)) = step_marker(line) {
+            self.numbered(number, body);
+        } else if let Some(body) = bullet(line) {
+            self.bullet(body);
+        } else {
+            self.text(line, previous_blank);
+        }
+    }
+
+    fn is_first(&self) -> bool {
+        self.title.is_none() && self.groups.is_empty() && self.steps.is_empty()
+    }
+
+    /// A heading, which can only be the title when it doesn't end in a colon
+    pub(crate) fn heading(&mut self, heading: ParserInput<'a>, can_be_title: bool) {
+        let name = heading.input.to_lowercase();
+        if INGREDIENT_HEADINGS.contains(&name.as_str()) {
+            self.section = Section::Ingredients(true);
+        } else if STEP_HEADINGS.contains(&name.as_str()) {
+            self.section = Section::Steps;
+        } else if self.is_first() && can_be_title {
+            self.title = Some(heading.span());
+        } else if self.section != Section::Steps {
+            self.groups.push((Some(heading.span()), vec![]));
+            self.section = Section::Ingredients(true);
+        }
+    }
+
+    pub(crate) fn numbered(&mut self, number: u32, body: ParserInput<'a>) {
+        self.steps.push((Some(number), body.span()));
+        self.section = Section::Steps;
+    }
+
+    pub(crate) fn bullet(&mut self, body: ParserInput<'a>) {
+        match self.section {
+            Section::Steps => self.steps.push((None, body.span())),
+            _ => self.ingredient(body),
+        }
+    }
+
+    fn text(&mut self, line: ParserInput<'a>, previous_blank: bool) {
+        match self.section {
+            Section::Steps => match self.steps.last_mut() {
+                // a step that wraps onto the next line
+                Some((_, step)) if !previous_blank => {
+                    step.chars.end = line.range().end;
+                    step.bytes.end = line.span().bytes.end;
+                }
+                _ => self.steps.push((None, line.span())),
+            },
+            Section::Ingredients(explicit) => {
+                // without a heading, the ingredients end at the first paragraph that doesn't
+                // start with a measure
+                if !explicit && previous_blank && !self.starts_with_measure(line) {
+                    self.section = Section::Steps;
+                    self.steps.push((None, line.span()));
+                } else {
+                    self.ingredient(line);
+                }
+            }
+            Section::Unknown if self.starts_with_measure(line) => {
+                self.section = Section::Ingredients(false);
+                self.ingredient(line);
+            }
+            Section::Unknown if self.is_first() => self.title = Some(line.span()),
+            // a description between the title and the ingredients
+            Section::Unknown => {}
+        }
+    }
+
+    fn ingredient(&mut self, line: ParserInput) {
+        match self.groups.last_mut() {
+            Some((_, lines)) => lines.push(line.span()),
+            None => self.groups.push((None, vec![line.span()])),
+        }
+    }
+
+    fn starts_with_measure(&self, line: ParserInput) -> bool {
+        let start = line.range().start;
+        self.recipe
+            .tokens
+            .iter()
+            .any(|token| token.full_range().start == start)
+    }
+
+    fn block(&self, span: Span) -> Block<'r, 'a> {
+        let tokens = &self.recipe.tokens;
+        let start = tokens.partition_point(|token| token.full_range().start < span.chars.start);
+        let end = tokens.partition_point(|token| token.full_range().end <= span.chars.end);
+
+        Block {
+            text: &self.recipe.raw[span.bytes],
+            range: span.chars,
+            tokens: &tokens[start..end.max(start)],
+        }
+    }
+
+    pub(crate) fn build(self) -> Structure<'r, 'a> {
+        Structure {
+            title: self.title.clone().map(|span| self.block(span)),
+            ingredient_groups: self
+                .groups
+                .iter()
+                .map(|(heading, lines)| IngredientGroup {
+                    heading: heading.clone().map(|span| self.block(span).text),
+                    lines: lines.iter().map(|span| self.block(span.clone())).collect(),
+                })
+                .collect(),
+            steps: self
+                .steps
+                .iter()
+                .map(|(number, span)| Step {
+                    number: *number,
+                    body: self.block(span.clone()),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Drop the whitespace around a line, [`None`] if that's all there is
+pub(crate) fn trim(line: ParserInput) -> Option<ParserInput> {
+    let start = line.input.len() - line.input.trim_start().len();
+    let trimmed = line.advance(start);
+    let text = trimmed.input.trim_end();
+
+    (!text.is_empty()).then(|| ParserInput::new(text, trimmed.position))
+}
+
+/// The text of a heading line (`# Pancakes`, `For the sauce:`), without the markup around it
+fn heading(line: ParserInput) -> Option<ParserInput> {
+    let text = line.input;
+    let heading = if let Some(hashes) = text.strip_prefix('#') {
+        let rest = hashes.trim_start_matches('#');
+        line.advance(text.len() - rest.len())
+    } else if let Some(text) = text.strip_suffix(':') {
+        ParserInput::new(text, line.position)
+    } else {
+        return None;
+    };
+
+    trim(heading)
+}
+
+/// The text after a bullet (`- `, `* `, `• `)
+fn bullet(line: ParserInput) -> Option<ParserInput> {
+    let (body, _) = tuple((one_of::<_, _, ParseError>("-*•"), space1))(line).ok()?;
+
+    Some(body)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::parse_recipe;
+    use crate::{ParseError, Unit};
+
+    const PANCAKES: &str = "# Pancakes
+
+Fluffy and quick.
+
+Ingredients:
+For the batter:
+- 1 ½ cups flour
+- 2 tbsp sugar
+For the topping:
+- 3 tbsp maple syrup
+
+Method:
+1. Whisk the flour and sugar
+   until combined.
+2. 3 eggs go in next.
+Step 3: Drizzle with syrup.
+";
+
+    #[test]
+    fn test_structure() {
+        let recipe = parse_recipe(PANCAKES).unwrap();
+        let structure = recipe.structure();
+
+        assert_eq!(structure.title.unwrap().text, "Pancakes");
+
+        let groups = &structure.ingredient_groups;
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].heading, Some("For the batter"));
+        assert_eq!(
+            groups[0]
+                .lines
+                .iter()
+                .map(|line| line.text)
+                .collect::<Vec<_>>(),
+            vec!["1 ½ cups flour", "2 tbsp sugar"]
+        );
+        assert_eq!(groups[0].lines[1].tokens.len(), 1);
+        assert_eq!(groups[1].heading, Some("For the topping"));
+        assert_eq!(
+            groups[1].lines[0].tokens[0].measure.main_unit(),
+            &Unit::Tablespoon
+        );
+
+        let steps = &structure.steps;
+        assert_eq!(
+            steps
+                .iter()
+                .map(|step| (step.number, step.body.text))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some(1), "Whisk the flour and sugar\n   until combined."),
+                (Some(2), "3 eggs go in next."),
+                (Some(3), "Drizzle with syrup."),
+            ]
+        );
+        assert_eq!(steps[1].body.tokens.len(), 1);
+        assert_eq!(
+            steps[1].body.tokens[0].measure.main_unit(),
+            &Unit::unitless("eggs".to_string())
+        );
+    }
+
+    #[test]
+    fn test_unmarked_structure() {
+        let recipe =
+            parse_recipe("Simple Rice\n2 cups rice\n3 cups water\n\nBoil the water. Add the rice.")
+                .unwrap();
+        let structure = recipe.structure();
+
+        assert_eq!(structure.title.unwrap().text, "Simple Rice");
+        assert_eq!(structure.ingredient_groups[0].heading, None);
+        assert_eq!(structure.ingredient_groups[0].lines.len(), 2);
+        assert_eq!(structure.steps.len(), 1);
+        assert_eq!(structure.steps[0].number, None);
+        assert_eq!(structure.steps[0].body.range, 38..67);
+    }
+
+    #[test]
+    fn test_oversized_step_number() {
+        let recipe = parse_recipe("Mix 2 cups\n99999999999. Add 3 eggs").unwrap();
+        assert_eq!(recipe.tokens.len(), 2);
+        assert!(matches!(
+            recipe.warnings[..],
+            [ParseError::NumberTooLarge { .. }]
+        ));
+
+        let recipe = parse_recipe("Mix\n3000000000 eggs\nthen 2 cups milk").unwrap();
+        assert_eq!(recipe.tokens.len(), 1);
+        assert_eq!(recipe.tokens[0].measure.main_unit(), &Unit::Cup);
+        assert!(matches!(
+            recipe.warnings[..],
+            [ParseError::NumberTooLarge { .. }]
+        ));
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::{Unit, UNITFUL_UNITS};
+
+/// No alias is anywhere near this long once it's lowercased, so longer words can't be units
+const MAX_ALIAS_BYTES: usize = 32;
+
+lazy_static! {
+    static ref UNIT_INDEX: UnitIndex = UnitIndex::new();
+}
+
+/// Every unit an alias could refer to, worked out once up front so looking up a unit doesn't have
+/// to walk every alias of every unit
+struct UnitIndex {
+    /// The candidates for text that is exactly an alias, matches with the same case first
+    exact: HashMap<&'static str, Vec<Unit>>,
+    /// The candidates for text that only matches an alias once both are lowercased
+    folded: HashMap<String, Vec<Unit>>,
+}
+
+impl UnitIndex {
+    fn new() -> UnitIndex {
+        let mut exact: HashMap<&'static str, Vec<Unit>> = HashMap::new();
+        let mut folded: HashMap<String, Vec<Unit>> = HashMap::new();
+        for unit in UNITFUL_UNITS.iter() {
+            for &alias in unit.aliases() {
+                push_unique(exact.entry(alias).or_default(), unit);
+                let lowercase = alias.to_lowercase();
+                debug_assert!(lowercase.len() <= MAX_ALIAS_BYTES);
+                push_unique(folded.entry(lowercase).or_default(), unit);
+            }
+        }
+
+        // secondary matches always come after exact matches (for cases like t & T)
+        for (alias, units) in exact.iter_mut() {
+            for unit in &folded[&alias.to_lowercase()] {
+                push_unique(units, unit);
+            }
+        }
+
+        UnitIndex { exact, folded }
+    }
+}
+
+fn push_unique(units: &mut Vec<Unit>, unit: &Unit) {
+    if !units.contains(unit) {
+        units.push(unit.clone());
+    }
+}
+
+/// Every unit `word` could refer to with the most likely one first, or [`None`] if it isn't an
+/// alias of any unit. This doesn't allocate.
+pub(crate) fn lookup_unit(word: &str) -> Option<&'static [Unit]> {
+    if let Some(units) = UNIT_INDEX.exact.get(word) {
+        return Some(units);
+    }
+
+    let mut buffer = [0; MAX_ALIAS_BYTES];
+    let lowercase = lowercase_into(word, &mut buffer)?;
+    UNIT_INDEX.folded.get(lowercase).map(Vec::as_slice)
+}
+
+/// Lowercase `word` into `buffer`, or [`None`] if it doesn't fit
+fn lowercase_into<'b>(word: &str, buffer: &'b mut [u8; MAX_ALIAS_BYTES]) -> Option<&'b str> {
+    let mut len = 0;
+    for c in word.chars().flat_map(char::to_lowercase) {
+        let end = len + c.len_utf8();
+        c.encode_utf8(buffer.get_mut(len..end)?);
+        len = end;
+    }
+
+    std::str::from_utf8(&buffer[..len]).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lookup_unit() {
+        assert_eq!(lookup_unit("cups"), Some(&[Unit::Cup][..]));
+        assert_eq!(lookup_unit("CUPS"), Some(&[Unit::Cup][..]));
+        assert_eq!(lookup_unit("°f"), Some(&[Unit::Fahrenheit][..]));
+        assert_eq!(
+            lookup_unit("T"),
+            Some(&[Unit::Tablespoon, Unit::Teaspoon][..])
+        );
+        assert_eq!(
+            lookup_unit("t"),
+            Some(&[Unit::Teaspoon, Unit::Tablespoon][..])
+        );
+        assert_eq!(lookup_unit("c"), Some(&[Unit::Cup, Unit::Celsius][..]));
+        assert_eq!(lookup_unit("flour"), None);
+        assert_eq!(lookup_unit(&"a".repeat(100)), None);
+    }
+}
@@ -0,0 +1,251 @@
+use std::fmt::Write;
+
+use roxmltree::{Document, Node};
+
+use crate::archive::yield_parts;
+use crate::schema_org::parse_yield_text;
+use crate::{ArchiveError, ArchivedGroup, ArchivedIngredient, ArchivedRecipe, Fraction};
+
+/// Read every `<recipe>` in a RecipeML document
+pub fn read_recipeml(xml: &str) -> Result<Vec<ArchivedRecipe>, ArchiveError> {
+    let document = Document::parse(xml)?;
+
+    Ok(document
+        .descendants()
+        .filter(|node| node.has_tag_name("recipe"))
+        .map(read_recipe)
+        .collect())
+}
+
+/// Write recipes out as a RecipeML document
+pub fn write_recipeml(recipes: &[ArchivedRecipe]) -> String {
+    let mut output = String::new();
+    write_document(&mut output, recipes).unwrap();
+
+    output
+}
+
+fn read_recipe(recipe: Node) -> ArchivedRecipe {
+    let head = child(recipe, "head");
+    let title = head.and_then(|head| child(head, "title"));
+    let categories = head
+        .and_then(|head| child(head, "categories"))
+        .map(|categories| children(categories, "cat").map(text).collect())
+        .unwrap_or_default();
+    let yields = head.and_then(|head| child(head, "yield")).and_then(|node| {
+        match (child(node, "qty"), child(node, "unit")) {
+            (Some(quantity), unit) => parse_yield_text(&format!(
+                "{} {}",
+                text(quantity),
+                unit.map(text).unwrap_or_default()
+            )),
+            (None, _) => parse_yield_text(&text(node)),
+        }
+    });
+
+    let mut ingredient_groups: Vec<ArchivedGroup> = vec![];
+    for node in child(recipe, "ingredients").iter().flat_map(Node::children) {
+        if node.has_tag_name("ing") {
+            match ingredient_groups.last_mut() {
+                Some(group) if group.heading.is_none() => group.ingredients.push(ingredient(node)),
+                _ => ingredient_groups.push(ArchivedGroup {
+                    heading: None,
+                    ingredients: vec![ingredient(node)],
+                }),
+            }
+        } else if node.has_tag_name("ing-div") {
+            ingredient_groups.push(ArchivedGroup {
+                heading: child(node, "title").map(text),
+                ingredients: children(node, "ing").map(ingredient).collect(),
+            });
+        }
+    }
+
+    // some files put their directions straight in `<directions>`, a paragraph per step
+    let directions = child(recipe, "directions")
+        .map(|directions| match children(directions, "step").next() {
+            Some(_) => children(directions, "step").map(text).collect(),
+            None => text(directions)
+                .split("\n\n")
+                .map(|paragraph| paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|paragraph| !paragraph.is_empty())
+                .collect(),
+        })
+        .unwrap_or_default();
+
+    ArchivedRecipe {
+        title: title.map(text).unwrap_or_default(),
+        categories,
+        yields,
+        ingredient_groups,
+        directions,
+    }
+}
+
+fn ingredient(ing: Node) -> ArchivedIngredient {
+    let amount = child(ing, "amt");
+    let field = |name| {
+        amount
+            .and_then(|amount| child(amount, name))
+            .map(text)
+            .unwrap_or_default()
+    };
+
+    ArchivedIngredient::new(
+        &field("qty"),
+        &field("unit"),
+        &child(ing, "item").map(text).unwrap_or_default(),
+        child(ing, "prep").map(text).as_deref(),
+    )
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |child| child.has_tag_name(name))
+}
+
+/// All the text inside an element, with the whitespace around it trimmed
+fn text(node: Node) -> String {
+    node.descendants()
+        .filter_map(|node| node.text().filter(|_| node.is_text()))
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn write_document(output: &mut String, recipes: &[ArchivedRecipe]) -> std::fmt::Result {
+    writeln!(output, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(output, r#"<recipeml version="0.5">"#)?;
+    for recipe in recipes {
+        writeln!(output, "  <recipe>")?;
+        writeln!(output, "    <head>")?;
+        writeln!(output, "      <title>{}</title>", escape(&recipe.title))?;
+        if !recipe.categories.is_empty() {
+            writeln!(output, "      <categories>")?;
+            for category in &recipe.categories {
+                writeln!(output, "        <cat>{}</cat>", escape(category))?;
+            }
+            writeln!(output, "      </categories>")?;
+        }
+        if let Some((amount, noun)) = recipe.yields.as_ref().and_then(yield_parts) {
+            writeln!(
+                output,
+                "      <yield><qty>{}</qty><unit>{}</unit></yield>",
+                Fraction(amount),
+                escape(noun)
+            )?;
+        }
+        writeln!(output, "    </head>")?;
+
+        writeln!(output, "    <ingredients>")?;
+        for group in &recipe.ingredient_groups {
+            match &group.heading {
+                Some(heading) => {
+                    writeln!(output, "      <ing-div>")?;
+                    writeln!(output, "        <title>{}</title>", escape(heading))?;
+                    for ingredient in &group.ingredients {
+                        write_ingredient(output, ingredient, "        ")?;
+                    }
+                    writeln!(output, "      </ing-div>")?;
+                }
+                None => {
+                    for ingredient in &group.ingredients {
+                        write_ingredient(output, ingredient, "      ")?;
+                    }
+                }
+            }
+        }
+        writeln!(output, "    </ingredients>")?;
+
+        writeln!(output, "    <directions>")?;
+        for direction in &recipe.directions {
+            writeln!(output, "      <step>{}</step>", escape(direction))?;
+        }
+        writeln!(output, "    </directions>")?;
+        writeln!(output, "  </recipe>")?;
+    }
+    writeln!(output, "</recipeml>")
+}
+
+fn write_ingredient(
+    output: &mut String,
+    ingredient: &ArchivedIngredient,
+    indent: &str,
+) -> std::fmt::Result {
+    write!(output, "{indent}<ing>")?;
+    if !ingredient.quantity.is_empty() || !ingredient.unit.is_empty() {
+        write!(
+            output,
+            "<amt><qty>{}</qty><unit>{}</unit></amt>",
+            escape(&ingredient.quantity),
+            escape(&ingredient.unit)
+        )?;
+    }
+    write!(output, "<item>{}</item>", escape(&ingredient.name))?;
+    if let Some(notes) = &ingredient.notes {
+        write!(output, "<prep>{}</prep>", escape(notes))?;
+    }
+    writeln!(output, "</ing>")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod test {
+    use num_rational::Rational32;
+
+    use crate::{read_mealmaster, Measure, Unit};
+
+    use super::*;
+
+    const COOKIES: &str = include_str!("../test_data/cookies.rml");
+
+    #[test]
+    fn test_read_recipeml() {
+        let recipes = read_recipeml(COOKIES).unwrap();
+        assert_eq!(recipes.len(), 1);
+
+        let cookies = &recipes[0];
+        assert_eq!(cookies.title, "Chocolate Chip Cookies");
+        assert_eq!(
+            cookies.ingredient_groups[0].ingredients[3].measure,
+            Some(Measure::single(Rational32::new(3, 2), Unit::Teaspoon))
+        );
+        assert_eq!(
+            cookies.ingredient_groups[1].ingredients[1].measure,
+            Some(Measure::single(
+                Rational32::from_integer(2),
+                Unit::Tablespoon
+            ))
+        );
+
+        // the same recipe as the MealMaster sample
+        let mealmaster = read_mealmaster(include_str!("../test_data/cookies.mmf"));
+        assert_eq!(recipes[0], mealmaster[0]);
+
+        assert!(matches!(
+            read_recipeml("<recipeml><recipe>"),
+            Err(ArchiveError::Xml(_))
+        ));
+    }
+
+    #[test]
+    fn test_recipeml_round_trip() {
+        let recipes = read_recipeml(COOKIES).unwrap();
+        let written = write_recipeml(&recipes);
+
+        assert_eq!(written, COOKIES);
+        assert_eq!(read_recipeml(&written).unwrap(), recipes);
+    }
+}
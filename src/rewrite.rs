@@ -0,0 +1,161 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use num_rational::Rational32;
+use num_traits::One;
+
+use crate::parser::{CharIndexing, MeasureToken, Recipe};
+use crate::{Decimal, Fraction, Measure, UnitSystem};
+
+/// A recipe with some of its measures replaced
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Rewrite {
+    pub text: String,
+    pub changes: Vec<MeasureChange>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MeasureChange {
+    /// The chars the measure covered in the original recipe
+    pub range: Range<usize>,
+    pub original: Measure,
+    pub measure: Measure,
+    /// The text that took the place of the measure
+    pub replacement: String,
+}
+
+impl<'a> Recipe<'a> {
+    /// Replace every measure that `rewrite` returns a new measure for, keeping the rest of the
+    /// text and as much of the way the measure was written as we can
+    pub fn rewrite<F>(&self, mut rewrite: F) -> Rewrite
+    where
+        F: FnMut(&MeasureToken<'a>) -> Option<Measure>,
+    {
+        let mut changes = vec![];
+        let text = self.render_with(|token| match rewrite(token) {
+            Some(measure) if measure != token.measure => {
+                let replacement = render_measure(token, &measure);
+                changes.push(MeasureChange {
+                    range: token.full_range(),
+                    original: token.measure.clone(),
+                    measure,
+                    replacement: replacement.clone(),
+                });
+
+                Cow::Owned(replacement)
+            }
+            _ => token.text(),
+        });
+
+        Rewrite { text, changes }
+    }
+}
+
+/// Write `measure` in place of `token`, keeping the spacing and modifiers between the number and
+/// unit, and the unit text if the unit didn't change
+pub(crate) fn render_measure(token: &MeasureToken, measure: &Measure) -> String {
+    let Measure::Single(single) = measure else {
+        return format!("{measure:#}");
+    };
+
+    let between = token
+        .raw
+        .char_slice(
+            token.number_range.end - token.number_range.start
+                ..token.unit_range.start - token.number_range.start,
+        )
+        .unwrap_or_default();
+    let plural = single.value > Rational32::one();
+    let unit_text = if single.unit == *token.measure.main_unit() {
+        let unit_text = token.unit_text();
+        // fix up the plural if we wrote out the full unit name
+        if unit_text.eq_ignore_ascii_case(single.unit.description(!plural)) {
+            Cow::Borrowed(single.unit.description(plural))
+        } else {
+            unit_text
+        }
+    } else if between.is_empty() && !is_full_name(token) {
+        // an abbreviation stuck to the number (`350°F`, `250g`) gets the new unit's symbol
+        Cow::Borrowed(single.unit.symbol())
+    } else {
+        Cow::Borrowed(single.unit.description(plural))
+    };
+
+    if single.unit.system() == Some(UnitSystem::Metric) {
+        format!("{}{between}{unit_text}", Decimal(single.value))
+    } else {
+        format!("{}{between}{unit_text}", Fraction(single.value))
+    }
+}
+
+fn is_full_name(token: &MeasureToken) -> bool {
+    let unit = token.measure.main_unit();
+    let unit_text = token.unit_text();
+    [true, false]
+        .iter()
+        .any(|&plural| unit_text.eq_ignore_ascii_case(unit.description(plural)))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::parse_recipe;
+    use crate::{SingleMeasure, Unit};
+
+    use super::*;
+
+    #[test]
+    fn test_rewrite() {
+        let recipe = parse_recipe("Add 1 cup milk, 2 large eggs and 3/4teaspoon salt").unwrap();
+        let rewrite = recipe.rewrite(|token| match token.measure.main_unit() {
+            Unit::Cup => Some(Measure::single(Rational32::from_integer(2), Unit::Cup)),
+            Unit::Teaspoon => Some(Measure::single(Rational32::one(), Unit::Tablespoon)),
+            _ => Some(token.measure.clone()),
+        });
+
+        assert_eq!(
+            rewrite.text,
+            "Add 2 cups milk, 2 large eggs and 1tablespoon salt"
+        );
+        assert_eq!(
+            rewrite.changes,
+            vec![
+                MeasureChange {
+                    range: 4..9,
+                    original: Measure::single(Rational32::one(), Unit::Cup),
+                    measure: Measure::single(Rational32::from_integer(2), Unit::Cup),
+                    replacement: "2 cups".to_string(),
+                },
+                MeasureChange {
+                    range: 33..44,
+                    original: Measure::single(Rational32::new(3, 4), Unit::Teaspoon),
+                    measure: Measure::single(Rational32::one(), Unit::Tablespoon),
+                    replacement: "1tablespoon".to_string(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_measure() {
+        let recipe = parse_recipe("1 ½ tsp").unwrap();
+        let token = &recipe.tokens[0];
+
+        assert_eq!(
+            render_measure(
+                token,
+                &Measure::single(Rational32::new(1, 3), Unit::Teaspoon)
+            ),
+            "1/3 tsp"
+        );
+        assert_eq!(
+            render_measure(
+                token,
+                &Measure::multi([
+                    SingleMeasure::new(Rational32::one(), Unit::Cup),
+                    SingleMeasure::new(Rational32::from_integer(2), Unit::Tablespoon),
+                ])
+            ),
+            "1 cup and 2 tablespoons"
+        );
+    }
+}
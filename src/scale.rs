@@ -0,0 +1,182 @@
+use num_rational::Rational32;
+use num_traits::{CheckedDiv, CheckedMul};
+
+use crate::parser::{CharIndexing, Recipe};
+use crate::{Dimension, Fraction, Magnitude, Measure, MeasureChange, Rewrite, Unit, UnitSystem};
+
+impl<'a> Recipe<'a> {
+    /// Multiply every volume, mass and count in the recipe by `factor`, writing each in whatever
+    /// unit reads best. Temperatures and times are left alone.
+    pub fn scale<R: Into<Rational32>>(&self, factor: R) -> Rewrite {
+        let factor = factor.into();
+        self.rewrite(|token| scale_measure(&token.measure, factor))
+    }
+
+    /// Scale the recipe so it makes `servings` (or however many of the thing it makes, like
+    /// cookies), updating the yield line to match. [`None`] if the recipe doesn't say what it
+    /// makes.
+    pub fn scale_to_servings<R: Into<Rational32>>(&self, servings: R) -> Option<Rewrite> {
+        let yields = self.yields.as_ref()?;
+        let servings = servings.into();
+        let factor = servings.checked_div(&yields.amount)?;
+        let mut rewrite = self.scale(factor);
+
+        // the changes are in order, so only the ones before the yield move it
+        let before = rewrite
+            .changes
+            .iter()
+            .take_while(|change| change.range.end <= yields.number_range.start)
+            .count();
+        let shift = rewrite.changes[..before]
+            .iter()
+            .map(|change| change.replacement.chars().count() as isize - change.range.len() as isize)
+            .sum::<isize>();
+        let start = (yields.number_range.start as isize + shift) as usize;
+        let end = start + yields.number_range.len();
+
+        let replacement = Fraction(servings).to_string();
+        let text = rewrite.text.as_str();
+        let prefix = text.char_slice(0..start).unwrap_or_default();
+        let suffix = text
+            .char_slice(end..text.chars().count())
+            .unwrap_or_default();
+        rewrite.text = format!("{prefix}{replacement}{suffix}");
+
+        let noun = yields.noun.as_deref().unwrap_or("servings");
+        rewrite.changes.insert(
+            before,
+            MeasureChange {
+                range: yields.number_range.clone(),
+                original: Measure::single(yields.amount, Unit::unitless(noun.to_string())),
+                measure: Measure::single(servings, Unit::unitless(noun.to_string())),
+                replacement,
+            },
+        );
+
+        Some(rewrite)
+    }
+}
+
+/// Scale a single measure, returning [`None`] if it shouldn't or can't be scaled
+pub(crate) fn scale_measure(measure: &Measure, factor: Rational32) -> Option<Measure> {
+    match measure.dimension() {
+        Dimension::Volume | Dimension::Mass => {
+            let magnitude = Magnitude::checked_from(measure)?.checked_mul(factor)?;
+            if measure.main_unit().system() == Some(UnitSystem::Metric) {
+                return magnitude.rounded_measure_in(UnitSystem::Metric);
+            }
+
+            let same_unit = magnitude.checked_measure(measure.main_unit().clone())?;
+            match magnitude.best_measure() {
+                // a whole number of the unit the recipe used reads better than a fraction of a
+                // bigger unit (2 tsp rather than 2/3 tablespoon)
+                Some(best) if best.is_integer() || !same_unit.is_integer() => Some(best),
+                _ => Some(same_unit),
+            }
+        }
+        Dimension::Unitless => match measure {
+            Measure::Single(single) => Some(Measure::single(
+                single.value.checked_mul(&factor)?,
+                single.unit.clone(),
+            )),
+            Measure::Multi(_) => None,
+        },
+        // a pan twice the width holds four times as much, so we can't scale lengths
+        Dimension::Temperature | Dimension::Time | Dimension::Length => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::parse_recipe;
+    use crate::Unit;
+
+    use super::*;
+
+    #[test]
+    fn test_scale() {
+        let recipe = parse_recipe(
+            "Mix 1 cup flour, 1 tsp salt and 3 large eggs. Bake at 350°F for 20 minutes.",
+        )
+        .unwrap();
+
+        let doubled = recipe.scale(2);
+        assert_eq!(
+            doubled.text,
+            "Mix 2 cups flour, 2 tsp salt and 6 large eggs. Bake at 350°F for 20 minutes."
+        );
+        assert_eq!(doubled.changes.len(), 3);
+
+        let halved = recipe.scale(Rational32::new(1, 2));
+        assert_eq!(
+            halved.text,
+            "Mix 1/2 cup flour, 1/2 tsp salt and 1 1/2 large eggs. Bake at 350°F for 20 minutes."
+        );
+        assert_eq!(
+            halved.changes[0].measure,
+            Measure::single(Rational32::new(1, 2), Unit::Cup)
+        );
+
+        let tripled = recipe.scale(3);
+        assert_eq!(
+            tripled.text,
+            "Mix 3 cups flour, 1 tablespoon salt and 9 large eggs. Bake at 350°F for 20 minutes."
+        );
+    }
+
+    #[test]
+    fn test_scale_metric() {
+        let recipe = parse_recipe("Whisk 250 ml milk into 400 g flour").unwrap();
+
+        assert_eq!(
+            recipe.scale(Rational32::new(1, 3)).text,
+            "Whisk 83 ml milk into 135 g flour"
+        );
+        assert_eq!(
+            recipe.scale(4).text,
+            "Whisk 1 liter milk into 1.6 kilograms flour"
+        );
+    }
+
+    #[test]
+    fn test_scale_to_servings() {
+        let recipe = parse_recipe("Serves 4\n\n2 cups rice\n1 tbsp butter").unwrap();
+        let rewrite = recipe.scale_to_servings(6).unwrap();
+
+        assert_eq!(rewrite.text, "Serves 6\n\n3 cups rice\n1 1/2 tbsp butter");
+        assert_eq!(rewrite.changes.len(), 3);
+        assert_eq!(rewrite.changes[0].range, 7..8);
+
+        let recipe = parse_recipe("Add ½ cup sugar.\nMakes 24 cookies").unwrap();
+        assert_eq!(
+            recipe.scale_to_servings(12).unwrap().text,
+            "Add 1/4 cup sugar.\nMakes 12 cookies"
+        );
+
+        assert!(parse_recipe("2 cups rice")
+            .unwrap()
+            .scale_to_servings(2)
+            .is_none());
+    }
+
+    #[test]
+    fn test_scale_unchanged() {
+        let recipe = parse_recipe("Simmer 2 cups stock for 10 minutes").unwrap();
+        let rewrite = recipe.scale(1);
+
+        assert_eq!(rewrite.text, "Simmer 2 cups stock for 10 minutes");
+        assert!(rewrite.changes.is_empty());
+
+        // measures too big for our numbers are left alone
+        let recipe = parse_recipe("100000 gallons water, 20000 gallons milk, 2 cups rice").unwrap();
+        assert_eq!(
+            recipe.scale(2).text,
+            "100000 gallons water, 20000 gallons milk, 4 cups rice"
+        );
+        let recipe = parse_recipe("Serves 4\n2000000000 cups water").unwrap();
+        assert_eq!(
+            recipe.scale_to_servings(8).unwrap().text,
+            "Serves 8\n2000000000 cups water"
+        );
+    }
+}
@@ -0,0 +1,539 @@
+use num_rational::Rational32;
+use num_traits::{CheckedAdd, CheckedMul, Zero};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::parser::{parse_ingredient_line, parse_number, Ingredient, ParserInput, Recipe};
+use crate::{Decimal, Dimension, Fraction, Magnitude, Measure, ParseError, SingleMeasure, Unit};
+
+const CONTEXT: &str = "https://schema.org";
+
+#[derive(Error, Debug)]
+pub enum SchemaOrgError {
+    #[error("invalid JSON-LD: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("no schema.org Recipe found")]
+    NoRecipe,
+    #[error("invalid ISO 8601 duration `{0}`")]
+    Duration(String),
+}
+
+/// Fields that can be a single value or a list of them
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> Default for OneOrMany<T> {
+    fn default() -> Self {
+        OneOrMany::Many(vec![])
+    }
+}
+
+impl<T> OneOrMany<T> {
+    pub fn is_empty(&self) -> bool {
+        matches!(self, OneOrMany::Many(many) if many.is_empty())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        match self {
+            OneOrMany::One(one) => std::slice::from_ref(one).iter(),
+            OneOrMany::Many(many) => many.iter(),
+        }
+    }
+}
+
+/// The parts of a [schema.org Recipe](https://schema.org/Recipe) we understand
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaRecipe {
+    /// Usually `https://schema.org`, but can be an object (`{"@vocab": ...}`) or a list
+    #[serde(rename = "@context", default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<serde_json::Value>,
+    #[serde(rename = "@type")]
+    pub schema_type: OneOrMany<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipe_yield: Option<OneOrMany<YieldValue>>,
+    #[serde(default, skip_serializing_if = "OneOrMany::is_empty")]
+    pub recipe_ingredient: OneOrMany<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipe_instructions: Option<OneOrMany<Instruction>>,
+    /// ISO 8601 durations, like `PT1H30M`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prep_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cook_time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_time: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum YieldValue {
+    Number(serde_json::Number),
+    Text(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(untagged)]
+pub enum Instruction {
+    Text(String),
+    Section(HowToSection),
+    Step(HowToStep),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HowToSection {
+    #[serde(rename = "@type")]
+    pub schema_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub item_list_element: Vec<Instruction>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct HowToStep {
+    #[serde(rename = "@type")]
+    pub schema_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Some sites only give the step a `name`, which we use instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+impl SchemaRecipe {
+    /// Find the Recipe in a page's JSON-LD, which can be the top level object, in a list, or in
+    /// an `@graph`
+    pub fn from_json_ld(json: &str) -> Result<SchemaRecipe, SchemaOrgError> {
+        let value = serde_json::from_str::<serde_json::Value>(json)?;
+        let recipe = find_recipe(&value).ok_or(SchemaOrgError::NoRecipe)?;
+
+        Ok(serde_json::from_value(recipe.clone())?)
+    }
+
+    pub fn to_json_ld(&self) -> String {
+        serde_json::to_string_pretty(self).expect("recipes always serialize")
+    }
+
+    /// Run each `recipeIngredient` through the ingredient parser
+    pub fn ingredients(&self) -> Vec<Result<Ingredient<'_>, ParseError>> {
+        self.recipe_ingredient
+            .iter()
+            .map(|line| parse_ingredient_line(line))
+            .collect()
+    }
+
+    /// The instructions as plain text, in order, with sections flattened out
+    pub fn instructions(&self) -> Vec<&str> {
+        fn flatten<'i>(instruction: &'i Instruction, steps: &mut Vec<&'i str>) {
+            match instruction {
+                Instruction::Text(text) => steps.push(text),
+                Instruction::Step(step) => {
+                    steps.extend(step.text.as_deref().or(step.name.as_deref()))
+                }
+                Instruction::Section(section) => {
+                    for instruction in &section.item_list_element {
+                        flatten(instruction, steps);
+                    }
+                }
+            }
+        }
+
+        let mut steps = vec![];
+        for instruction in self.recipe_instructions.iter().flat_map(OneOrMany::iter) {
+            flatten(instruction, &mut steps);
+        }
+        steps
+    }
+
+    /// How much the recipe makes, as a count of servings or of whatever it names
+    /// (`24 cookies`)
+    pub fn yields(&self) -> Option<Measure> {
+        self.recipe_yield
+            .iter()
+            .flat_map(OneOrMany::iter)
+            .find_map(|value| match value {
+                // `4` and `4.0` are both servings
+                YieldValue::Number(number) => parse_yield_text(&number.to_string()),
+                YieldValue::Text(text) => parse_yield_text(text),
+            })
+    }
+
+    pub fn prep_time(&self) -> Option<Result<Measure, SchemaOrgError>> {
+        self.prep_time.as_deref().map(parse_duration)
+    }
+
+    pub fn cook_time(&self) -> Option<Result<Measure, SchemaOrgError>> {
+        self.cook_time.as_deref().map(parse_duration)
+    }
+
+    pub fn total_time(&self) -> Option<Result<Measure, SchemaOrgError>> {
+        self.total_time.as_deref().map(parse_duration)
+    }
+}
+
+fn find_recipe(value: &serde_json::Value) -> Option<&serde_json::Value> {
+    match value {
+        serde_json::Value::Array(values) => values.iter().find_map(find_recipe),
+        serde_json::Value::Object(object) => {
+            let is_recipe = match object.get("@type") {
+                Some(serde_json::Value::String(schema_type)) => schema_type == "Recipe",
+                Some(serde_json::Value::Array(types)) => types.iter().any(|t| t == "Recipe"),
+                _ => false,
+            };
+            if is_recipe {
+                Some(value)
+            } else {
+                object.get("@graph").and_then(find_recipe)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// `4 servings`, `Makes 24 cookies`, `6`
+pub(crate) fn parse_yield_text(text: &str) -> Option<Measure> {
+    let text = text.trim();
+    let start = text.find(|c: char| c.is_ascii_digit() || "¼½¾⅓⅔⅛".contains(c))?;
+    let (remainder, number) = parse_number(ParserInput::from(&text[start..])).ok()?;
+    let noun = remainder
+        .input
+        .split_whitespace()
+        .next()
+        .unwrap_or("servings")
+        .trim_matches(|c: char| !c.is_alphabetic());
+
+    Some(Measure::single(number, Unit::unitless(noun.to_string())))
+}
+
+/// Turn an ISO 8601 duration (`PT1H30M`, `P1DT2H`) into a time measure, as hours, minutes and
+/// seconds. We don't support years or months since they don't have a fixed length.
+pub fn parse_duration(duration: &str) -> Result<Measure, SchemaOrgError> {
+    let error = || SchemaOrgError::Duration(duration.to_string());
+    let rest = duration.trim().strip_prefix('P').ok_or_else(error)?;
+
+    let mut seconds = Rational32::zero();
+    let mut in_time = false;
+    let mut number_start = None;
+    for (i, c) in rest.char_indices() {
+        match c {
+            'T' if !in_time && number_start.is_none() => in_time = true,
+            '0'..='9' | '.' | ',' => {
+                number_start.get_or_insert(i);
+            }
+            designator => {
+                let start = number_start.take().ok_or_else(error)?;
+                let number = parse_duration_number(&rest[start..i]).ok_or_else(error)?;
+                let multiple = match (in_time, designator) {
+                    (false, 'W') => 7 * 24 * 60 * 60,
+                    (false, 'D') => 24 * 60 * 60,
+                    (true, 'H') => 60 * 60,
+                    (true, 'M') => 60,
+                    (true, 'S') => 1,
+                    _ => return Err(error()),
+                };
+                seconds = number
+                    .checked_mul(&Rational32::from_integer(multiple))
+                    .and_then(|part| seconds.checked_add(&part))
+                    .ok_or_else(error)?;
+            }
+        }
+    }
+    if number_start.is_some() || rest.is_empty() || rest.ends_with('T') {
+        return Err(error());
+    }
+
+    Ok(time_measure(seconds))
+}
+
+fn parse_duration_number(number: &str) -> Option<Rational32> {
+    let number = number.replace(',', ".");
+    let (remainder, value) = parse_number(ParserInput::from(number.as_str())).ok()?;
+    (remainder.input.is_empty()).then_some(value)
+}
+
+/// Split seconds into hours, minutes and seconds, dropping the parts that are zero
+fn time_measure(seconds: Rational32) -> Measure {
+    let mut remaining = seconds;
+    let mut parts = vec![];
+    for unit in [Unit::Hour, Unit::Minute] {
+        let multiple = unit.multiple();
+        let whole = (remaining / multiple).trunc();
+        if !whole.is_zero() {
+            parts.push(SingleMeasure::new(whole, unit));
+            remaining -= whole * multiple;
+        }
+    }
+    if !remaining.is_zero() || parts.is_empty() {
+        parts.push(SingleMeasure::new(remaining, Unit::Second));
+    }
+
+    match parts.len() {
+        1 => Measure::Single(parts.remove(0)),
+        _ => Measure::multi(parts),
+    }
+}
+
+/// Write a time measure as an ISO 8601 duration (`PT1H30M`), [`None`] if it isn't a time or is too
+/// long to count in seconds
+pub fn format_duration(measure: &Measure) -> Option<String> {
+    if measure.dimension() != Dimension::Time {
+        return None;
+    }
+    let seconds = Magnitude::checked_from(measure)?
+        .checked_measure(Unit::Second)?
+        .main_value();
+
+    let parts = match time_measure(seconds) {
+        Measure::Single(part) => vec![part],
+        Measure::Multi(parts) => parts,
+    };
+
+    let mut duration = "PT".to_string();
+    for part in &parts {
+        push_duration_part(&mut duration, part);
+    }
+
+    Some(duration)
+}
+
+fn push_duration_part(duration: &mut String, part: &SingleMeasure) {
+    let designator = match part.unit {
+        Unit::Hour => 'H',
+        Unit::Minute => 'M',
+        _ => 'S',
+    };
+    let value = if part.value.is_integer() {
+        part.value.to_integer().to_string()
+    } else {
+        Decimal(part.value).to_string()
+    };
+    duration.push_str(&format!("{value}{designator}"));
+}
+
+impl<'a> Recipe<'a> {
+    /// Describe the recipe as a schema.org Recipe, using its title, ingredients list and steps.
+    /// The total time is the sum of the times in the steps.
+    pub fn to_schema_org(&self) -> SchemaRecipe {
+        let structure = self.structure();
+
+        let recipe_ingredient = structure
+            .ingredient_groups
+            .iter()
+            .flat_map(|group| &group.lines)
+            .map(|line| line.text.to_string())
+            .collect::<Vec<_>>();
+        let steps = structure
+            .steps
+            .iter()
+            .map(|step| {
+                Instruction::Step(HowToStep {
+                    schema_type: "HowToStep".to_string(),
+                    name: None,
+                    text: Some(
+                        step.body
+                            .text
+                            .split_whitespace()
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    ),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let total_seconds = structure
+            .steps
+            .iter()
+            .flat_map(|step| step.body.tokens)
+            .filter(|token| token.measure.dimension() == Dimension::Time)
+            .try_fold(Magnitude::new(0, Unit::Second), |total, token| {
+                total.checked_add(Magnitude::checked_from(&token.measure)?)
+            })
+            .and_then(|total| total.checked_measure(Unit::Second))
+            .filter(|total| !total.main_value().is_zero());
+
+        let recipe_yield = self.yields.as_ref().map(|found| {
+            let amount = Fraction(found.amount).to_string();
+            OneOrMany::One(match &found.noun {
+                Some(noun) => YieldValue::Text(format!("{amount} {noun}")),
+                None => YieldValue::Text(amount),
+            })
+        });
+
+        SchemaRecipe {
+            context: Some(CONTEXT.into()),
+            schema_type: OneOrMany::One("Recipe".to_string()),
+            name: structure.title.map(|title| title.text.to_string()),
+            description: None,
+            recipe_yield,
+            recipe_ingredient: OneOrMany::Many(recipe_ingredient),
+            recipe_instructions: (!steps.is_empty()).then_some(OneOrMany::Many(steps)),
+            prep_time: None,
+            cook_time: None,
+            total_time: total_seconds.as_ref().and_then(format_duration),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parser::parse_recipe;
+
+    use super::*;
+
+    #[test]
+    fn test_import() {
+        let recipe = SchemaRecipe::from_json_ld(
+            r#"{
+                "@context": "https://schema.org",
+                "@graph": [
+                    {"@type": "WebPage", "name": "Some blog"},
+                    {
+                        "@type": ["Recipe"],
+                        "name": "Pancakes",
+                        "recipeYield": ["4", "4 servings"],
+                        "recipeIngredient": ["1 ½ cups flour", "2 large eggs", "salt to taste"],
+                        "recipeInstructions": [
+                            {"@type": "HowToSection", "name": "Batter", "itemListElement": [
+                                {"@type": "HowToStep", "text": "Whisk everything."}
+                            ]},
+                            "Cook until golden."
+                        ],
+                        "cookTime": "PT1H30M",
+                        "totalTime": "P1DT0.5S"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(recipe.name.as_deref(), Some("Pancakes"));
+        let ingredients = recipe.ingredients();
+        let flour = ingredients[0].as_ref().unwrap();
+        assert_eq!(
+            flour.measure.as_ref().unwrap().measure,
+            Measure::single(Rational32::new(3, 2), Unit::Cup)
+        );
+        assert_eq!(flour.name, "flour");
+        assert_eq!(ingredients[2].as_ref().unwrap().notes, Some("to taste"));
+        assert_eq!(
+            recipe.instructions(),
+            vec!["Whisk everything.", "Cook until golden."]
+        );
+        assert_eq!(
+            recipe.yields(),
+            Some(Measure::single(
+                Rational32::from_integer(4),
+                Unit::unitless("servings".to_string())
+            ))
+        );
+        assert_eq!(
+            recipe.cook_time().unwrap().unwrap(),
+            Measure::multi(vec![
+                SingleMeasure::new(Rational32::from_integer(1), Unit::Hour),
+                SingleMeasure::new(Rational32::from_integer(30), Unit::Minute),
+            ])
+        );
+        assert_eq!(
+            recipe.total_time().unwrap().unwrap(),
+            Measure::multi(vec![
+                SingleMeasure::new(Rational32::from_integer(24), Unit::Hour),
+                SingleMeasure::new(Rational32::new(1, 2), Unit::Second),
+            ])
+        );
+
+        assert!(matches!(
+            SchemaRecipe::from_json_ld(r#"{"@type": "WebPage"}"#),
+            Err(SchemaOrgError::NoRecipe)
+        ));
+    }
+
+    #[test]
+    fn test_import_loose_shapes() {
+        let recipe = SchemaRecipe::from_json_ld(
+            r#"{
+                "@context": {"@vocab": "https://schema.org/"},
+                "@type": "Recipe",
+                "recipeYield": 4.0,
+                "recipeIngredient": "1 cup flour",
+                "recipeInstructions": [{"@type": "HowToStep", "name": "Stir well."}]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            recipe.context,
+            Some(serde_json::json!({"@vocab": "https://schema.org/"}))
+        );
+        assert_eq!(recipe.ingredients().len(), 1);
+        assert_eq!(recipe.instructions(), vec!["Stir well."]);
+        assert_eq!(
+            recipe.yields(),
+            Some(Measure::single(
+                Rational32::from_integer(4),
+                Unit::unitless("servings".to_string())
+            ))
+        );
+
+        let recipe =
+            SchemaRecipe::from_json_ld(r#"{"@type": "Recipe", "recipeYield": "6"}"#).unwrap();
+        assert_eq!(
+            recipe.yields().unwrap().main_value(),
+            Rational32::from_integer(6)
+        );
+    }
+
+    #[test]
+    fn test_durations() {
+        let round_trip = |duration| format_duration(&parse_duration(duration).unwrap()).unwrap();
+        assert_eq!(round_trip("PT90M"), "PT1H30M");
+        assert_eq!(round_trip("PT45S"), "PT45S");
+        assert_eq!(round_trip("PT0,5H"), "PT30M");
+        assert_eq!(
+            format_duration(&Measure::single(
+                Rational32::from_integer(1_000_000),
+                Unit::Hour
+            )),
+            None
+        );
+        assert!(parse_duration("P1M").is_err());
+        assert!(parse_duration("PT").is_err());
+        assert!(parse_duration("1H").is_err());
+    }
+
+    #[test]
+    fn test_export() {
+        let recipe = parse_recipe(
+            "Pancakes\nServes 4\n\nIngredients:\n- 1 cup milk\n- 2 eggs\n\nMethod:\n\
+            1. Whisk the milk and eggs.\n2. Rest for 10 minutes, then cook for 1 hour.",
+        )
+        .unwrap();
+        let schema = recipe.to_schema_org();
+
+        assert_eq!(schema.name.as_deref(), Some("Pancakes"));
+        assert_eq!(
+            schema.recipe_ingredient,
+            OneOrMany::Many(vec!["1 cup milk".to_string(), "2 eggs".to_string()])
+        );
+        assert_eq!(schema.total_time.as_deref(), Some("PT1H10M"));
+
+        let json = schema.to_json_ld();
+        assert_eq!(SchemaRecipe::from_json_ld(&json).unwrap(), schema);
+        let value = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+        assert_eq!(value["@context"], "https://schema.org");
+        assert_eq!(value["@type"], "Recipe");
+        assert_eq!(value["recipeYield"], "4");
+        assert_eq!(
+            value["recipeInstructions"][1]["text"],
+            "Rest for 10 minutes, then cook for 1 hour."
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use num_rational::Rational32;
+use num_traits::{CheckedDiv, CheckedMul, CheckedSub, One, Signed, Zero};
+use serde::{Deserialize, Serialize};
+
+use crate::{Dimension, Magnitude, Measure, Unit};
+
+/// A system of measurement that a reader wants their recipe in
+#[derive(Serialize, Deserialize, Eq, PartialEq, Hash, Debug, Copy, Clone, Default)]
+pub enum UnitSystem {
+    /// Cups, ounces, Fahrenheit and inches
+    #[default]
+    UsCustomary,
+    /// Milliliters, grams, Celsius and centimeters
+    Metric,
+}
+
+/// How precisely to write a value in a unit
+#[derive(Debug, Copy, Clone)]
+enum Rounding {
+    /// The nearest eighth or third, whichever is closer
+    Fractions,
+    /// The nearest multiple of this
+    Step(i32, i32),
+    /// A couple significant figures, the way metric measures are usually written
+    Decimal,
+}
+
+/// The units we'd write a measure in, largest first, with the smallest value (as a fraction) we'd
+/// write in each unit before moving on to the next one
+fn preferred_units(
+    dimension: Dimension,
+    system: UnitSystem,
+) -> &'static [(Unit, (i32, i32), Rounding)] {
+    match (system, dimension) {
+        (UnitSystem::UsCustomary, Dimension::Volume) => &[
+            (Unit::Gallon, (1, 1), Rounding::Fractions),
+            (Unit::Cup, (1, 4), Rounding::Fractions),
+            (Unit::Tablespoon, (1, 1), Rounding::Fractions),
+            (Unit::Teaspoon, (0, 1), Rounding::Fractions),
+        ],
+        (UnitSystem::UsCustomary, Dimension::Mass) => &[
+            (Unit::Pound, (1, 1), Rounding::Step(1, 4)),
+            (Unit::Ounce, (0, 1), Rounding::Step(1, 4)),
+        ],
+        (UnitSystem::UsCustomary, Dimension::Length) => {
+            &[(Unit::Inch, (0, 1), Rounding::Step(1, 4))]
+        }
+        (UnitSystem::UsCustomary, Dimension::Temperature) => {
+            &[(Unit::Fahrenheit, (0, 1), Rounding::Step(5, 1))]
+        }
+        (UnitSystem::Metric, Dimension::Volume) => &[
+            (Unit::Liter, (1, 1), Rounding::Decimal),
+            (Unit::Milliliter, (0, 1), Rounding::Decimal),
+        ],
+        (UnitSystem::Metric, Dimension::Mass) => &[
+            (Unit::Kilogram, (1, 1), Rounding::Decimal),
+            (Unit::Gram, (0, 1), Rounding::Decimal),
+        ],
+        (UnitSystem::Metric, Dimension::Length) => &[
+            (Unit::Centimeter, (1, 1), Rounding::Decimal),
+            (Unit::Millimeter, (0, 1), Rounding::Decimal),
+        ],
+        (UnitSystem::Metric, Dimension::Temperature) => {
+            &[(Unit::Celsius, (0, 1), Rounding::Step(5, 1))]
+        }
+        (_, Dimension::Time | Dimension::Unitless) => &[],
+    }
+}
+
+impl Rounding {
+    /// Round `value`, or [`None`] if it's too big to round
+    fn round(self, value: Rational32) -> Option<Rational32> {
+        let nearest = |step: Rational32| value.checked_div(&step)?.round().checked_mul(&step);
+        match self {
+            Rounding::Fractions => {
+                let eighths = nearest(Rational32::new(1, 8))?;
+                let thirds = nearest(Rational32::new(1, 3))?;
+                if eighths.checked_sub(&value)?.abs() <= thirds.checked_sub(&value)?.abs() {
+                    Some(eighths)
+                } else {
+                    Some(thirds)
+                }
+            }
+            Rounding::Step(numer, denom) => nearest(Rational32::new(numer, denom)),
+            Rounding::Decimal => {
+                if value >= Rational32::from_integer(100) {
+                    nearest(Rational32::from_integer(5))
+                } else if value >= Rational32::from_integer(10) {
+                    nearest(Rational32::one())
+                } else {
+                    nearest(Rational32::new(1, 10))
+                }
+            }
+        }
+    }
+}
+
+impl Magnitude {
+    /// Write this magnitude in the unit of `system` that reads best, rounded to the precision
+    /// that system is usually written with. [`None`] if the dimension has no units in `system`
+    /// or the value is too small to write or too big for our numbers.
+    pub fn rounded_measure_in(self, system: UnitSystem) -> Option<Measure> {
+        let preferred = preferred_units(self.dimension(), system);
+        let (unit, _, rounding) = preferred
+            .iter()
+            .find(|(unit, (numer, denom), _)| {
+                self.checked_measure(unit.clone())
+                    .is_some_and(|measure| measure.main_value() >= Rational32::new(*numer, *denom))
+            })
+            .or(preferred.last())?;
+
+        let exact = self.checked_measure(unit.clone())?.main_value();
+        let value = rounding.round(exact)?;
+        (!value.is_zero() || exact.is_zero()).then(|| Measure::single(value, unit.clone()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rounded(value: i32, unit: Unit, system: UnitSystem) -> Option<String> {
+        Magnitude::new(value, unit)
+            .rounded_measure_in(system)
+            .map(|measure| format!("{measure:#}"))
+    }
+
+    #[test]
+    fn test_rounded_measure_in() {
+        let metric = UnitSystem::Metric;
+        let customary = UnitSystem::UsCustomary;
+        assert_eq!(
+            rounded(1, Unit::Cup, metric),
+            Some("240 milliliters".into())
+        );
+        assert_eq!(
+            rounded(1, Unit::Teaspoon, metric),
+            Some("5 milliliters".into())
+        );
+        assert_eq!(rounded(8, Unit::Cup, metric), Some("1.9 liters".into()));
+        assert_eq!(rounded(1, Unit::Pound, metric), Some("455 grams".into()));
+        assert_eq!(
+            rounded(350, Unit::Fahrenheit, metric),
+            Some("175 celsius".into())
+        );
+        assert_eq!(
+            rounded(9, Unit::Inch, metric),
+            Some("23 centimeters".into())
+        );
+        assert_eq!(
+            rounded(250, Unit::Milliliter, customary),
+            Some("1 cup".into())
+        );
+        assert_eq!(
+            rounded(30, Unit::Milliliter, customary),
+            Some("2 tablespoons".into())
+        );
+        assert_eq!(
+            rounded(2, Unit::Milliliter, customary),
+            Some("3/8 teaspoon".into())
+        );
+        assert_eq!(
+            rounded(100, Unit::Gram, customary),
+            Some("3 1/2 ounces".into())
+        );
+        assert_eq!(
+            rounded(1, Unit::Kilogram, customary),
+            Some("2 1/4 pounds".into())
+        );
+        assert_eq!(
+            rounded(200, Unit::Celsius, customary),
+            Some("390 fahrenheit".into())
+        );
+        assert_eq!(
+            rounded(20, Unit::Centimeter, customary),
+            Some("7 3/4 inches".into())
+        );
+        assert_eq!(rounded(10, Unit::Minute, metric), None);
+    }
+}
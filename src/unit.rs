@@ -3,10 +3,10 @@ use std::fmt::{Debug, Display, Formatter};
 
 use lazy_static::lazy_static;
 use num_rational::Rational32;
-use num_traits::One;
+use num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, One, Zero};
 use serde::{Deserialize, Serialize};
 
-use crate::Dimension;
+use crate::{Dimension, UnitSystem};
 
 #[derive(Serialize, Deserialize, Eq, PartialEq, Clone)]
 pub enum Unit {
@@ -15,11 +15,13 @@ pub enum Unit {
     Smidgen,
     Pinch,
     Dash,
+    Milliliter,
     Teaspoon,
     Tablespoon,
     Cup,
     Pint,
     Quart,
+    Liter,
     Gallon,
     // Temp
     Fahrenheit,
@@ -28,38 +30,64 @@ pub enum Unit {
     Second,
     Minute,
     Hour,
+    // Mass
+    Gram,
+    Ounce,
+    Pound,
+    Kilogram,
+    // Length
+    Millimeter,
+    Centimeter,
+    Inch,
     //
     Unitless { unit: String },
 }
 
-const VOLUME_UNITS_COUNT: usize = 10;
+const VOLUME_UNITS_COUNT: usize = 12;
 pub static VOLUME_UNITS: [Unit; VOLUME_UNITS_COUNT] = [
     Unit::Drop,
     Unit::Smidgen,
     Unit::Pinch,
     Unit::Dash,
+    Unit::Milliliter,
     Unit::Teaspoon,
     Unit::Tablespoon,
     Unit::Cup,
     Unit::Pint,
     Unit::Quart,
+    Unit::Liter,
     Unit::Gallon,
 ];
 const TEMPERATURE_UNITS_COUNT: usize = 2;
 pub static TEMPERATURE_UNITS: [Unit; TEMPERATURE_UNITS_COUNT] = [Unit::Fahrenheit, Unit::Celsius];
 const TIME_UNITS_COUNT: usize = 3;
 pub static TIME_UNITS: [Unit; TIME_UNITS_COUNT] = [Unit::Second, Unit::Minute, Unit::Hour];
+const MASS_UNITS_COUNT: usize = 4;
+pub static MASS_UNITS: [Unit; MASS_UNITS_COUNT] =
+    [Unit::Gram, Unit::Ounce, Unit::Pound, Unit::Kilogram];
+const LENGTH_UNITS_COUNT: usize = 3;
+pub static LENGTH_UNITS: [Unit; LENGTH_UNITS_COUNT] =
+    [Unit::Millimeter, Unit::Centimeter, Unit::Inch];
 pub static UNITLESS_UNITS: [Unit; 1] = [Unit::unitless(String::new())];
 
+const UNITFUL_UNITS_COUNT: usize = VOLUME_UNITS_COUNT
+    + TEMPERATURE_UNITS_COUNT
+    + TIME_UNITS_COUNT
+    + MASS_UNITS_COUNT
+    + LENGTH_UNITS_COUNT;
+
 lazy_static! {
-    pub static ref UNITFUL_UNITS: [Unit; VOLUME_UNITS_COUNT + TEMPERATURE_UNITS_COUNT + TIME_UNITS_COUNT] = {
-        let mut unitful: [Unit; VOLUME_UNITS_COUNT + TEMPERATURE_UNITS_COUNT + TIME_UNITS_COUNT] =
-            Default::default();
+    pub static ref UNITFUL_UNITS: [Unit; UNITFUL_UNITS_COUNT] = {
+        let mut unitful: [Unit; UNITFUL_UNITS_COUNT] = Default::default();
         let (volume, remainder) = unitful.split_at_mut(VOLUME_UNITS_COUNT);
-        let (temperature, time) = remainder.split_at_mut(TEMPERATURE_UNITS_COUNT);
+        let (temperature, remainder) = remainder.split_at_mut(TEMPERATURE_UNITS_COUNT);
+        let (time, remainder) = remainder.split_at_mut(TIME_UNITS_COUNT);
+        let (mass, length) = remainder.split_at_mut(MASS_UNITS_COUNT);
         volume.clone_from_slice(&VOLUME_UNITS);
         temperature.clone_from_slice(&TEMPERATURE_UNITS);
         time.clone_from_slice(&TIME_UNITS);
+        mass.clone_from_slice(&MASS_UNITS);
+        length.clone_from_slice(&LENGTH_UNITS);
 
         unitful
     };
@@ -73,11 +101,20 @@ impl Unit {
             Unit::Smidgen => &["smidgen", "smidgens", "smi", "smdg"],
             Unit::Pinch => &["pinch", "pinches", "pn"],
             Unit::Dash => &["dash", "dashes", "ds"],
+            Unit::Milliliter => &[
+                "milliliter",
+                "milliliters",
+                "millilitre",
+                "millilitres",
+                "mL",
+                "ml",
+            ],
             Unit::Teaspoon => &["teaspoon", "teaspoons", "t", "tsp"],
             Unit::Tablespoon => &["tablespoon", "tablespoons", "Tb", "T", "tbsp"],
             Unit::Cup => &["cup", "cups", "c", "C"],
             Unit::Pint => &["pint", "pints", "pt"],
             Unit::Quart => &["quart", "quarts", "qt"],
+            Unit::Liter => &["liter", "liters", "litre", "litres", "l", "L"],
             Unit::Gallon => &["gallon", "gallons", "gal"],
             //  Temp
             Unit::Fahrenheit => &[
@@ -87,11 +124,33 @@ impl Unit {
                 "°F",
                 "F",
             ],
-            Unit::Celsius => &["celsius", "celsius", "°C", "C"],
+            Unit::Celsius => &["celsius", "celsius", "degrees", "°C", "C"],
             // Time
             Unit::Second => &["second", "seconds", "sec"],
             Unit::Minute => &["minute", "minutes", "min"],
             Unit::Hour => &["hour", "hours"],
+            // Mass
+            Unit::Gram => &["gram", "grams", "g"],
+            Unit::Ounce => &["ounce", "ounces", "oz"],
+            Unit::Pound => &["pound", "pounds", "lbs", "lb"],
+            Unit::Kilogram => &["kilogram", "kilograms", "kg"],
+            // Length
+            Unit::Millimeter => &[
+                "millimeter",
+                "millimeters",
+                "millimetre",
+                "millimetres",
+                "mm",
+            ],
+            Unit::Centimeter => &[
+                "centimeter",
+                "centimeters",
+                "centimetre",
+                "centimetres",
+                "cm",
+            ],
+            // "in" shows up in too many sentences to be an alias
+            Unit::Inch => &["inch", "inches"],
             //
             Unit::Unitless { .. } => &[""],
         }
@@ -101,6 +160,15 @@ impl Unit {
         self.aliases().last().unwrap()
     }
 
+    /// What to write right after a number, like `350°F` or `250g`
+    pub fn symbol(&self) -> &str {
+        match self {
+            Unit::Fahrenheit => "°F",
+            Unit::Celsius => "°C",
+            _ => self.abbreviation(),
+        }
+    }
+
     pub fn description(&self, plural: bool) -> &str {
         if plural {
             // TODO this is a gross hack
@@ -116,7 +184,25 @@ impl Unit {
 
     #[allow(clippy::wrong_self_convention)]
     pub(crate) fn from_base_value(&self, base_value: Rational32) -> Rational32 {
-        base_value / self.multiple()
+        (base_value - self.offset()) / self.multiple()
+    }
+
+    pub(crate) fn base_value(&self, value: Rational32) -> Rational32 {
+        value * self.multiple() + self.offset()
+    }
+
+    /// [`Unit::base_value`], or [`None`] if it doesn't fit in our numbers
+    pub(crate) fn checked_base_value(&self, value: Rational32) -> Option<Rational32> {
+        value
+            .checked_mul(&self.multiple())?
+            .checked_add(&self.offset())
+    }
+
+    /// [`Unit::from_base_value`], or [`None`] if it doesn't fit in our numbers
+    pub(crate) fn checked_from_base_value(&self, base_value: Rational32) -> Option<Rational32> {
+        base_value
+            .checked_sub(&self.offset())?
+            .checked_div(&self.multiple())
     }
 
     pub(crate) fn multiple(&self) -> Rational32 {
@@ -126,41 +212,95 @@ impl Unit {
             Unit::Smidgen => Rational32::from_integer(3),
             Unit::Pinch => Rational32::from_integer(6),
             Unit::Dash => Rational32::from_integer(12),
+            // We use the US legal cup of 240 mL so spoons come out to 5 mL and 15 mL
+            Unit::Milliliter => Rational32::new(96, 5),
             Unit::Teaspoon => Rational32::from_integer(96),
             Unit::Tablespoon => Rational32::from_integer(288),
             Unit::Cup => Rational32::from_integer(4_608),
             Unit::Pint => Rational32::from_integer(9_216),
             Unit::Quart => Rational32::from_integer(18_432),
+            Unit::Liter => Rational32::from_integer(19_200),
             Unit::Gallon => Rational32::from_integer(73_728),
             // Temp
-            Unit::Fahrenheit | Unit::Celsius => Rational32::one(),
+            Unit::Fahrenheit => Rational32::one(),
+            Unit::Celsius => Rational32::new(9, 5),
             // Time
             Unit::Second => Rational32::one(),
             Unit::Minute => Rational32::from_integer(60),
             Unit::Hour => Rational32::from_integer(60 * 60),
+            // Mass
+            Unit::Gram => Rational32::one(),
+            Unit::Ounce => Rational32::new(567, 20),
+            Unit::Pound => Rational32::new(2_268, 5),
+            Unit::Kilogram => Rational32::from_integer(1_000),
+            // Length
+            Unit::Millimeter => Rational32::one(),
+            Unit::Centimeter => Rational32::from_integer(10),
+            Unit::Inch => Rational32::new(127, 5),
             //
             Unit::Unitless { .. } => Rational32::one(),
         }
     }
 
+    /// Temperatures don't start at the same zero, so our base value is in Fahrenheit
+    pub(crate) fn offset(&self) -> Rational32 {
+        match self {
+            Unit::Celsius => Rational32::from_integer(32),
+            _ => Rational32::zero(),
+        }
+    }
+
     pub fn dimension(&self) -> Dimension {
         match self {
             Unit::Drop
             | Unit::Smidgen
             | Unit::Pinch
             | Unit::Dash
+            | Unit::Milliliter
             | Unit::Teaspoon
             | Unit::Tablespoon
             | Unit::Cup
             | Unit::Pint
             | Unit::Quart
+            | Unit::Liter
             | Unit::Gallon => Dimension::Volume,
             Unit::Second | Unit::Minute | Unit::Hour => Dimension::Time,
             Unit::Fahrenheit | Unit::Celsius => Dimension::Temperature,
+            Unit::Gram | Unit::Ounce | Unit::Pound | Unit::Kilogram => Dimension::Mass,
+            Unit::Millimeter | Unit::Centimeter | Unit::Inch => Dimension::Length,
             Unit::Unitless { .. } => Dimension::Unitless,
         }
     }
 
+    /// The system of measurement this unit belongs to, [`None`] for units that every system
+    /// shares
+    pub fn system(&self) -> Option<UnitSystem> {
+        match self {
+            Unit::Drop
+            | Unit::Smidgen
+            | Unit::Pinch
+            | Unit::Dash
+            | Unit::Teaspoon
+            | Unit::Tablespoon
+            | Unit::Cup
+            | Unit::Pint
+            | Unit::Quart
+            | Unit::Gallon
+            | Unit::Fahrenheit
+            | Unit::Ounce
+            | Unit::Pound
+            | Unit::Inch => Some(UnitSystem::UsCustomary),
+            Unit::Milliliter
+            | Unit::Liter
+            | Unit::Celsius
+            | Unit::Gram
+            | Unit::Kilogram
+            | Unit::Millimeter
+            | Unit::Centimeter => Some(UnitSystem::Metric),
+            Unit::Second | Unit::Minute | Unit::Hour | Unit::Unitless { .. } => None,
+        }
+    }
+
     pub const fn unitless(unit: String) -> Unit {
         Unit::Unitless { unit }
     }